@@ -1,3 +1,5 @@
+use solana_instruction_error::InstructionError;
+use solana_transaction_error::TransactionError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,12 +7,107 @@ pub enum ShadowOracleError {
     #[error("Price feed not found: {0}")]
     PriceFeedNotFound(String),
 
+    #[error("Price feed already exists: {0}")]
+    FeedAlreadyExists(String),
+
+    #[error("Price feeds not found: {}", .0.join(", "))]
+    MultipleFeedsNotFound(Vec<String>),
+
+    #[error("Failed to create price feeds: {}", .0.join("; "))]
+    FeedCreationFailed(Vec<String>),
+
+    #[error("Feed {feed} belongs to {found}, not {expected}")]
+    ProviderMismatch {
+        feed: String,
+        expected: String,
+        found: String,
+    },
+
     #[error("Invalid price data: {0}")]
     InvalidPriceData(String),
 
     #[error("Failed to serialize account: {0}")]
     SerializationError(String),
 
+    #[error("SVM rejected account: {0}")]
+    SvmError(String),
+
     #[error("Provider not available: {0}")]
     ProviderNotAvailable(String),
 }
+
+impl ShadowOracleError {
+    /// Map to the `InstructionError` a program would most likely fail with
+    ///
+    /// | Variant                                           | `InstructionError`          |
+    /// | ------------------------------------------------- | --------------------------- |
+    /// | `PriceFeedNotFound`, `MultipleFeedsNotFound`      | `UninitializedAccount`      |
+    /// | `FeedAlreadyExists`                               | `AccountAlreadyInitialized` |
+    /// | `ProviderMismatch`                                | `InvalidAccountOwner`       |
+    /// | `InvalidPriceData`, `SerializationError`,         | `InvalidAccountData`        |
+    /// | `FeedCreationFailed`                              |                             |
+    /// | `SvmError`                                        | `InvalidArgument`           |
+    /// | `ProviderNotAvailable`                            | `IncorrectProgramId`        |
+    pub fn to_instruction_error(&self) -> InstructionError {
+        match self {
+            Self::PriceFeedNotFound(_) | Self::MultipleFeedsNotFound(_) => {
+                InstructionError::UninitializedAccount
+            }
+            Self::FeedAlreadyExists(_) => InstructionError::AccountAlreadyInitialized,
+            Self::ProviderMismatch { .. } => InstructionError::InvalidAccountOwner,
+            Self::InvalidPriceData(_)
+            | Self::SerializationError(_)
+            | Self::FeedCreationFailed(_) => InstructionError::InvalidAccountData,
+            Self::SvmError(_) => InstructionError::InvalidArgument,
+            Self::ProviderNotAvailable(_) => InstructionError::IncorrectProgramId,
+        }
+    }
+
+    /// Map to a `TransactionError` failing the first instruction, for
+    /// negative-path assertions in program-test style suites
+    ///
+    /// The instruction error follows
+    /// [`to_instruction_error`](Self::to_instruction_error). The error itself
+    /// converts into `Box<dyn std::error::Error>` with `?` like any error type.
+    ///
+    /// # Example
+    /// ```
+    /// use shadow_oracle::ShadowOracleError;
+    /// use solana_instruction_error::InstructionError;
+    /// use solana_transaction_error::TransactionError;
+    ///
+    /// let err = ShadowOracleError::PriceFeedNotFound("feed".to_string());
+    /// assert_eq!(
+    ///     err.to_transaction_error(),
+    ///     TransactionError::InstructionError(0, InstructionError::UninitializedAccount)
+    /// );
+    /// ```
+    pub fn to_transaction_error(&self) -> TransactionError {
+        TransactionError::InstructionError(0, self.to_instruction_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_transaction_error() {
+        let err = ShadowOracleError::ProviderMismatch {
+            feed: "feed".to_string(),
+            expected: "Pyth".to_string(),
+            found: "Chainlink".to_string(),
+        };
+        assert_eq!(
+            err.to_transaction_error(),
+            TransactionError::InstructionError(0, InstructionError::InvalidAccountOwner)
+        );
+        assert_eq!(
+            ShadowOracleError::InvalidPriceData("bad".to_string()).to_instruction_error(),
+            InstructionError::InvalidAccountData
+        );
+
+        let boxed: Box<dyn std::error::Error> = ShadowOracleError::SvmError("x".to_string()).into();
+        assert_eq!(boxed.to_string(), "SVM rejected account: x");
+    }
+}
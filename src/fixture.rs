@@ -0,0 +1,133 @@
+//! Declarative setup of multi-asset markets
+
+use crate::{Asset, Provider, ShadowOracle};
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// A feed to create as part of a [`MarketFixture`]
+#[derive(Debug, Clone)]
+struct FixtureFeed {
+    asset: Asset,
+    provider: Provider,
+    price: f64,
+    conf: f64,
+}
+
+/// Builder for a set of feeds, one per asset, created in one go
+///
+/// Each feed is labeled with its asset's symbol, so it shows up by name in
+/// descriptions and errors.
+///
+/// # Example
+/// ```rust
+/// use litesvm::LiteSVM;
+/// use shadow_oracle::{Asset, MarketFixture, Provider, ShadowOracle};
+///
+/// let mut svm = LiteSVM::new().with_sysvars();
+/// let mut oracle = ShadowOracle::new(&mut svm);
+///
+/// let feeds = MarketFixture::new()
+///     .add(Asset::Sol, Provider::Pyth, 150.0, 0.3)
+///     .add("USDC", Provider::Chainlink, 1.0, 0.0)
+///     .build(&mut oracle);
+///
+/// let (price, _) = oracle.pyth().get_price_usd(&feeds[&Asset::Sol]).unwrap();
+/// assert_eq!(price, 150.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MarketFixture {
+    feeds: Vec<FixtureFeed>,
+}
+
+impl MarketFixture {
+    /// Create an empty fixture
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a feed for `asset` on `provider` at the given USD price and confidence
+    ///
+    /// `asset` can be an [`Asset`] or a symbol such as `"SOL"`.
+    pub fn add(
+        mut self,
+        asset: impl Into<Asset>,
+        provider: Provider,
+        price: f64,
+        conf: f64,
+    ) -> Self {
+        self.feeds.push(FixtureFeed {
+            asset: asset.into(),
+            provider,
+            price,
+            conf,
+        });
+        self
+    }
+
+    /// Create every feed and return their addresses by asset
+    ///
+    /// Feeds are created in the order they were added, through
+    /// [`ShadowOracle::create_market`]. If an asset was added more than once,
+    /// the map holds the last feed created for it.
+    ///
+    /// # Panics
+    /// Panics if any feed fails to create, e.g. when the oracle's template
+    /// sets out-of-range decimals.
+    pub fn build(&self, oracle: &mut ShadowOracle<'_>) -> HashMap<Asset, Pubkey> {
+        let spec: Vec<_> = self
+            .feeds
+            .iter()
+            .map(|feed| (feed.provider, feed.asset.clone(), feed.price, feed.conf))
+            .collect();
+        let market = oracle
+            .create_market(&spec)
+            .unwrap_or_else(|err| panic!("Failed to build market: {err}"));
+        spec.into_iter()
+            .map(|(provider, asset, ..)| {
+                let feed = market[&(provider, asset.clone())];
+                (asset, feed)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litesvm::LiteSVM;
+
+    #[test]
+    fn test_build_market() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feeds = MarketFixture::new()
+            .add(Asset::Sol, Provider::Pyth, 150.0, 0.3)
+            .add("BTC", Provider::Switchboard, 43000.0, 10.0)
+            .add("USDC", Provider::Chainlink, 1.0, 0.0)
+            .add("JUP", Provider::Pyth, 0.9, 0.001)
+            .build(&mut oracle);
+
+        assert_eq!(feeds.len(), 4);
+        let (sol, _) = oracle.pyth().get_price_usd(&feeds[&Asset::Sol]).unwrap();
+        let (btc, _) = oracle
+            .switchboard()
+            .get_price_usd(&feeds[&Asset::Btc])
+            .unwrap();
+        let (usdc, _) = oracle
+            .chainlink()
+            .get_price_usd(&feeds[&Asset::Usdc])
+            .unwrap();
+        assert!((sol - 150.0).abs() < 0.001);
+        assert!((btc - 43000.0).abs() < 0.001);
+        assert!((usdc - 1.0).abs() < 0.001);
+
+        assert_eq!(
+            oracle.pyth().label(&feeds[&Asset::Sol]),
+            Some("SOL".to_string())
+        );
+        let jup = &feeds[&Asset::Other("JUP".to_string())];
+        assert_eq!(oracle.pyth().label(jup), Some("JUP".to_string()));
+        assert_eq!(oracle.feed_count(Provider::Switchboard), 1);
+    }
+}
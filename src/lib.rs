@@ -36,43 +36,738 @@
 //! ```
 
 mod error;
+mod fixture;
 mod price;
 pub mod providers;
+#[cfg(feature = "serde")]
+mod snapshot;
 
 pub use error::*;
+pub use fixture::MarketFixture;
 pub use price::*;
 pub use providers::chainlink::Chainlink;
 pub use providers::pyth::Pyth;
 pub use providers::switchboard::Switchboard;
+pub use providers::Provider;
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
 
 use litesvm::LiteSVM;
+use providers::chainlink::ChainlinkState;
+use providers::pyth::PythState;
+use providers::switchboard::SwitchboardState;
+use solana_account::Account;
+use solana_clock::Clock;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Native loader, owner of the stub accounts created by
+/// [`ShadowOracle::register_program`]
+const NATIVE_LOADER_ID: &str = "NativeLoader1111111111111111111111111111111";
 
 /// Main entry point for shadow oracles
 ///
-/// Provides access to all oracle providers through a single interface.
+/// Provides access to all oracle providers through a single interface. Feeds
+/// created through a provider handle stay tracked by the `ShadowOracle`, so a
+/// later `oracle.pyth()` call can update them.
 pub struct ShadowOracle<'a> {
     svm: &'a mut LiteSVM,
+    template: PriceConf,
+    pyth: PythState,
+    switchboard: SwitchboardState,
+    chainlink: ChainlinkState,
+    indices: HashMap<Pubkey, IndexFeed>,
+}
+
+/// Hash of a feed's account data at one point in time, taken by
+/// [`ShadowOracle::checkpoint`] and checked by
+/// [`ShadowOracle::assert_unchanged`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedCheckpoint {
+    feed: Pubkey,
+    /// Byte ranges zeroed before hashing, empty for an exact checkpoint
+    volatile: Vec<Range<usize>>,
+    /// `None` if the account did not exist
+    hash: Option<[u8; 32]>,
+}
+
+impl FeedCheckpoint {
+    /// The checkpointed account
+    pub fn feed(&self) -> Pubkey {
+        self.feed
+    }
+}
+
+/// Constituents of a composite index feed
+struct IndexFeed {
+    provider: Provider,
+    constituents: Vec<(Pubkey, f64)>,
 }
 
 impl<'a> ShadowOracle<'a> {
     /// Create a new ShadowOracle instance
     pub fn new(svm: &'a mut LiteSVM) -> Self {
-        Self { svm }
+        Self {
+            svm,
+            template: PriceConf::default(),
+            pyth: PythState::default(),
+            switchboard: SwitchboardState::default(),
+            chainlink: ChainlinkState::default(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Run `f` with a `ShadowOracle` borrowing `svm`, releasing the borrow afterwards
+    ///
+    /// Returns whatever `f` returns, typically the feed addresses it created,
+    /// so the SVM is free again for transactions once setup is done.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    ///
+    /// let feed = ShadowOracle::scoped(&mut svm, |oracle| {
+    ///     oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1))
+    /// });
+    ///
+    /// assert!(svm.get_account(&feed).is_some());
+    /// ```
+    pub fn scoped<R>(svm: &mut LiteSVM, f: impl FnOnce(&mut ShadowOracle<'_>) -> R) -> R {
+        f(&mut ShadowOracle::new(svm))
+    }
+
+    /// Reborrow the underlying SVM, e.g. to send a transaction between price updates
+    ///
+    /// Unlike [`scoped`](Self::scoped), this keeps the oracle and its feed
+    /// registry alive.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// let feed = oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// oracle.svm().warp_to_slot(100);
+    /// oracle.pyth().set_price_usd(&feed, 90.0, 0.1).unwrap();
+    /// ```
+    pub fn svm(&mut self) -> &mut LiteSVM {
+        self.svm
+    }
+
+    /// Set the SVM clock to an exact time and slot
+    ///
+    /// Every provider stamps feeds from the SVM `Clock` sysvar, so feeds
+    /// created or updated afterwards carry exactly this timestamp and slot.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// oracle.set_clock(1_700_000_000, 1_000);
+    /// let feed = oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// assert_eq!(oracle.pyth().get_timestamp(&feed), Some(1_700_000_000));
+    /// ```
+    pub fn set_clock(&mut self, unix_timestamp: i64, slot: u64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        clock.slot = slot;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Load an executable stub account at `program_id`, so a program that
+    /// loads an oracle's owner account and checks that it is executable passes
+    ///
+    /// The stub is owned by the native loader and holds no bytecode: it only
+    /// satisfies owner lookups, and invoking it fails. An account that is
+    /// already executable, such as a real program you deployed, is left as is.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{Pyth, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// oracle.register_program(&Pyth::program_id_default()).unwrap();
+    /// let program = oracle.svm().get_account(&Pyth::program_id_default()).unwrap();
+    /// assert!(program.executable);
+    /// ```
+    pub fn register_program(&mut self, program_id: &Pubkey) -> Result<(), ShadowOracleError> {
+        if self
+            .svm
+            .get_account(program_id)
+            .is_some_and(|account| account.executable)
+        {
+            return Ok(());
+        }
+        self.svm
+            .set_account(
+                *program_id,
+                Account {
+                    lamports: 1_000_000_000,
+                    data: b"shadow-oracle program stub".to_vec(),
+                    owner: Pubkey::from_str(NATIVE_LOADER_ID).unwrap(),
+                    executable: true,
+                    rent_epoch: 0,
+                },
+            )
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+
+    /// Use `template` for the exponent and decimals of feeds created by any provider
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle =
+    ///     ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_expo(-6));
+    ///
+    /// // $100 is rescaled to the template's exponent
+    /// let feed = oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// assert_eq!(oracle.pyth().get_price(&feed), Some((100_000_000, 100_000)));
+    /// ```
+    pub fn with_template(mut self, template: PriceConf) -> Self {
+        self.template = template;
+        self
     }
 
     /// Get a Pyth oracle provider
     pub fn pyth(&mut self) -> Pyth<'_> {
-        Pyth::new(self.svm)
+        Pyth::from_state(self.svm, &mut self.pyth).with_template(self.template.clone())
     }
 
     /// Get a Switchboard oracle provider
     pub fn switchboard(&mut self) -> Switchboard<'_> {
-        Switchboard::new(self.svm)
+        Switchboard::from_state(self.svm, &mut self.switchboard)
+            .with_template(self.template.clone())
     }
 
     /// Get a Chainlink oracle provider
     pub fn chainlink(&mut self) -> Chainlink<'_> {
-        Chainlink::new(self.svm)
+        Chainlink::from_state(self.svm, &mut self.chainlink).with_template(self.template.clone())
+    }
+
+    /// Get the number of feeds tracked for `provider`
+    pub fn feed_count(&self, provider: Provider) -> usize {
+        match provider {
+            Provider::Pyth => self.pyth.len(),
+            Provider::Switchboard => self.switchboard.len(),
+            Provider::Chainlink => self.chainlink.len(),
+        }
+    }
+
+    /// List every feed across all providers with its current USD price
+    ///
+    /// Sorted by provider, then by address.
+    pub fn all_feeds(&self) -> Vec<(Provider, Pubkey, f64)> {
+        let feeds = [
+            (Provider::Pyth, self.pyth.feeds().collect::<Vec<_>>()),
+            (Provider::Switchboard, self.switchboard.feeds().collect()),
+            (Provider::Chainlink, self.chainlink.feeds().collect()),
+        ];
+        let mut all: Vec<_> = feeds
+            .into_iter()
+            .flat_map(|(provider, feeds)| {
+                feeds.into_iter().filter_map(move |feed| {
+                    let (price, _) = self.provider_price_usd(provider, feed)?;
+                    Some((provider, *feed, price))
+                })
+            })
+            .collect();
+        all.sort_by_key(|(provider, feed, _)| (*provider, *feed));
+        all
+    }
+
+    /// Create the standard feeds (SOL, BTC, ETH, USDC, USDT) on every provider
+    ///
+    /// All three sets start at the same prices.
+    pub fn create_all_standard_feeds(&mut self) -> AllStandardFeeds {
+        AllStandardFeeds {
+            pyth: self.pyth().create_standard_feeds(),
+            switchboard: self.switchboard().create_standard_feeds(),
+            chainlink: self.chainlink().create_standard_feeds(),
+        }
+    }
+
+    /// Create a labeled feed for each `(provider, asset, price, conf)` entry
+    ///
+    /// Prices and confidences are in USD, and each feed is labeled with its
+    /// asset's symbol. Returns the feeds keyed by provider and asset; a
+    /// repeated key maps to the last feed created for it. Every entry is
+    /// attempted: if any fail, the error is `FeedCreationFailed` listing each
+    /// failure, and the entries that succeeded stay created.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{Asset, Provider, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// let feeds = oracle
+    ///     .create_market(&[
+    ///         (Provider::Pyth, Asset::Sol, 150.0, 0.3),
+    ///         (Provider::Chainlink, Asset::Usdc, 1.0, 0.0),
+    ///     ])
+    ///     .unwrap();
+    ///
+    /// let sol = feeds[&(Provider::Pyth, Asset::Sol)];
+    /// assert_eq!(oracle.pyth().get_price_usd(&sol).unwrap().0, 150.0);
+    /// ```
+    pub fn create_market(
+        &mut self,
+        spec: &[(Provider, Asset, f64, f64)],
+    ) -> Result<HashMap<(Provider, Asset), Pubkey>, ShadowOracleError> {
+        let mut feeds = HashMap::new();
+        let mut failures = Vec::new();
+        for (provider, asset, price, conf) in spec {
+            let symbol = asset.to_string();
+            let conf = PriceConf::new_usd(*price, *conf);
+            let result = match provider {
+                Provider::Pyth => self.pyth().try_create_price_feed_labeled(&symbol, conf),
+                Provider::Switchboard => self
+                    .switchboard()
+                    .try_create_price_feed_labeled(&symbol, conf),
+                Provider::Chainlink => self
+                    .chainlink()
+                    .try_create_price_feed_labeled(&symbol, conf),
+            };
+            match result {
+                Ok(feed) => {
+                    feeds.insert((*provider, asset.clone()), feed);
+                }
+                Err(err) => failures.push(format!("{symbol} on {provider:?}: {err}")),
+            }
+        }
+        if failures.is_empty() {
+            Ok(feeds)
+        } else {
+            Err(ShadowOracleError::FeedCreationFailed(failures))
+        }
+    }
+
+    /// Spread between two feeds on `provider` in basis points
+    ///
+    /// Returns `(a - b) / b * 10_000` using USD prices, so feeds with
+    /// different exponents compare correctly. `None` if either feed is not
+    /// tracked or `b` is zero.
+    pub fn spread_bps(&self, provider: Provider, feed_a: &Pubkey, feed_b: &Pubkey) -> Option<f64> {
+        let (a, _) = self.provider_price_usd(provider, feed_a)?;
+        let (b, _) = self.provider_price_usd(provider, feed_b)?;
+        (b != 0.0).then(|| (a - b) / b * 10_000.0)
+    }
+
+    /// Create one feed per provider from the same config
+    ///
+    /// Returns the Pyth, Switchboard and Chainlink feeds, in that order. All
+    /// three read back the same USD price.
+    pub fn create_all(&mut self, conf: PriceConf) -> (Pubkey, Pubkey, Pubkey) {
+        let pyth = self.pyth().create_price_feed(conf.clone());
+        let switchboard = self.switchboard().create_price_feed(conf.clone());
+        let chainlink = self.chainlink().create_price_feed(conf);
+        (pyth, switchboard, chainlink)
+    }
+
+    /// Create a feed on `provider` priced as a weighted sum of other feeds
+    ///
+    /// Constituents may live on any provider. The index confidence is the
+    /// weighted sum of the constituent confidences (Chainlink feeds contribute
+    /// zero). Call [`refresh_index`](Self::refresh_index) after a constituent
+    /// moves.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, Provider, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// let sol = oracle.pyth().create_price_feed(PriceConf::new_usd(150.0, 0.1));
+    /// let eth = oracle.pyth().create_price_feed(PriceConf::new_usd(3000.0, 1.0));
+    /// let basket = oracle
+    ///     .create_index_feed(Provider::Pyth, &[(sol, 10.0), (eth, 0.5)])
+    ///     .unwrap();
+    ///
+    /// oracle.pyth().set_price_usd(&sol, 160.0, 0.1).unwrap();
+    /// oracle.refresh_index(&basket).unwrap();
+    /// ```
+    pub fn create_index_feed(
+        &mut self,
+        provider: Provider,
+        constituents: &[(Pubkey, f64)],
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let (price, conf) = self.index_price(constituents)?;
+        let conf = PriceConf::new_usd(price, conf);
+        let feed = match provider {
+            Provider::Pyth => self.pyth().create_price_feed(conf),
+            Provider::Switchboard => self.switchboard().create_price_feed(conf),
+            Provider::Chainlink => self.chainlink().create_price_feed(conf),
+        };
+        self.indices.insert(
+            feed,
+            IndexFeed {
+                provider,
+                constituents: constituents.to_vec(),
+            },
+        );
+        Ok(feed)
+    }
+
+    /// Recompute an index feed from the current prices of its constituents
+    pub fn refresh_index(&mut self, index_feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let index = self
+            .indices
+            .get(index_feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(index_feed.to_string()))?;
+        let provider = index.provider;
+        let (price, conf) = self.index_price(&index.constituents)?;
+        self.set_price_usd(provider, index_feed, price, conf)
+    }
+
+    /// Create a mock feed at the mainnet address of `asset`'s USD feed
+    ///
+    /// Looks the address up with `feeds::<provider>::by_asset`, so programs that
+    /// hardcode mainnet feed addresses read the mock. `asset` can be an
+    /// [`Asset`] or a symbol such as `"SOL/USD"`. Returns `None` for assets the
+    /// provider has no known address for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{feeds, Provider, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// let feed = oracle
+    ///     .mirror_mainnet_feed(Provider::Pyth, "SOL/USD", 150.0, 0.1)
+    ///     .unwrap();
+    /// assert_eq!(feed, feeds::pyth::sol_usd());
+    /// ```
+    pub fn mirror_mainnet_feed(
+        &mut self,
+        provider: Provider,
+        asset: impl Into<Asset>,
+        price_usd: f64,
+        conf: f64,
+    ) -> Option<Pubkey> {
+        let asset = asset.into();
+        let conf = PriceConf::new_usd(price_usd, conf);
+        let feed = match provider {
+            Provider::Pyth => {
+                let address = feeds::pyth::by_asset(&asset)?;
+                self.pyth().create_price_feed_at(address, conf)
+            }
+            Provider::Switchboard => {
+                let address = feeds::switchboard::by_asset(&asset)?;
+                self.switchboard().create_price_feed_at(address, conf)
+            }
+            Provider::Chainlink => {
+                let address = feeds::chainlink::by_asset(&asset)?;
+                self.chainlink().create_price_feed_at(address, conf)
+            }
+        };
+        Some(feed)
+    }
+
+    /// Simulate a market-wide risk-off move on standard feeds
+    ///
+    /// Crashes SOL, BTC and ETH by `crash_percent` while USDC and USDT hold
+    /// their peg. `provider` must be the provider `feeds` were created with.
+    pub fn simulate_risk_off(
+        &mut self,
+        provider: Provider,
+        feeds: &StandardFeeds,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let majors = [feeds.sol, feeds.btc, feeds.eth];
+        self.check_feeds(provider, &majors)?;
+        for feed in majors {
+            match provider {
+                Provider::Pyth => self.pyth().simulate_crash(&feed, crash_percent)?,
+                Provider::Switchboard => self.switchboard().simulate_crash(&feed, crash_percent)?,
+                Provider::Chainlink => self.chainlink().simulate_crash(&feed, crash_percent)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that feeds on different providers report the same USD price
+    ///
+    /// Returns an error naming the first pair of feeds whose prices differ by
+    /// more than `epsilon`, or any feed its provider does not track.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, Provider, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// let pyth = oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// let chainlink = oracle.chainlink().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    ///
+    /// oracle
+    ///     .assert_parity(&[(Provider::Pyth, pyth), (Provider::Chainlink, chainlink)], 0.01)
+    ///     .unwrap();
+    /// ```
+    pub fn assert_parity(&self, feeds: &[(Provider, Pubkey)], epsilon: f64) -> Result<(), String> {
+        let prices = feeds
+            .iter()
+            .map(|(provider, feed)| {
+                self.provider_price_usd(*provider, feed)
+                    .map(|(price, _)| (*provider, feed, price))
+                    .ok_or_else(|| format!("{provider:?} feed {feed} not found"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (i, (provider_a, feed_a, price_a)) in prices.iter().enumerate() {
+            for (provider_b, feed_b, price_b) in &prices[i + 1..] {
+                if (price_a - price_b).abs() > epsilon {
+                    return Err(format!(
+                        "{provider_a:?} feed {feed_a} reports ${price_a} but {provider_b:?} \
+                         feed {feed_b} reports ${price_b} (epsilon {epsilon})"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a feed's current account data, to check later that nothing wrote to it
+    ///
+    /// Every byte counts, so a republish at a new slot or timestamp is a
+    /// change; see [`checkpoint_stable`](Self::checkpoint_stable). Any address
+    /// can be checkpointed: a missing account is recorded as missing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    ///
+    /// let feed = oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// let checkpoint = oracle.checkpoint(&feed);
+    /// // ... run the instruction under test ...
+    /// assert!(oracle.assert_unchanged(&checkpoint));
+    /// ```
+    pub fn checkpoint(&self, feed: &Pubkey) -> FeedCheckpoint {
+        self.checkpoint_with(feed, Vec::new())
+    }
+
+    /// Like [`checkpoint`](Self::checkpoint), but ignoring the slot and
+    /// timestamp fields the tracking provider zeroes in `account_hash_stable`
+    ///
+    /// Use it when the test republishes the feed and only price data
+    /// matters. For an untracked address this is an exact checkpoint.
+    pub fn checkpoint_stable(&self, feed: &Pubkey) -> FeedCheckpoint {
+        let volatile = self
+            .provider_of(feed)
+            .map_or_else(Vec::new, volatile_fields);
+        self.checkpoint_with(feed, volatile)
+    }
+
+    /// Whether a feed's account data still matches `checkpoint`
+    ///
+    /// Only the data is compared, not lamports or owner. An account that was
+    /// created or closed since the checkpoint counts as changed.
+    pub fn assert_unchanged(&self, checkpoint: &FeedCheckpoint) -> bool {
+        self.account_hash(&checkpoint.feed, &checkpoint.volatile) == checkpoint.hash
+    }
+
+    fn checkpoint_with(&self, feed: &Pubkey, volatile: Vec<Range<usize>>) -> FeedCheckpoint {
+        FeedCheckpoint {
+            feed: *feed,
+            hash: self.account_hash(feed, &volatile),
+            volatile,
+        }
+    }
+
+    /// Hash of an account's data in the SVM, `None` if the account is missing
+    fn account_hash(&self, feed: &Pubkey, volatile: &[Range<usize>]) -> Option<[u8; 32]> {
+        let account = self.svm.get_account(feed)?;
+        Some(providers::account_hash(&account.data, volatile))
+    }
+
+    /// Publish the same USD price to several feeds one after another, as
+    /// oracles propagating a move with a delay
+    ///
+    /// Feeds are updated in order, the first at the current slot. Between
+    /// updates the SVM is warped `slot_gap` slots forward, so each feed's
+    /// slot is `slot_gap` past the previous one and the SVM ends
+    /// `slot_gap * (feeds.len() - 1)` slots ahead; the clock's timestamp is
+    /// not moved. Every feed is checked against its provider before anything
+    /// is written.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, Provider, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    /// let (pyth, _, chainlink) = oracle.create_all(PriceConf::new_usd(100.0, 0.1));
+    ///
+    /// oracle
+    ///     .set_price_staggered(&[(Provider::Pyth, pyth), (Provider::Chainlink, chainlink)], 90.0, 0.1, 3)
+    ///     .unwrap();
+    /// let lag = oracle.chainlink().get_slot(&chainlink).unwrap() - oracle.pyth().get_slot(&pyth).unwrap();
+    /// assert_eq!(lag, 3);
+    /// ```
+    pub fn set_price_staggered(
+        &mut self,
+        feeds: &[(Provider, Pubkey)],
+        price: f64,
+        confidence: f64,
+        slot_gap: u64,
+    ) -> Result<(), ShadowOracleError> {
+        for (provider, feed) in feeds {
+            self.check_feeds(*provider, &[*feed])?;
+        }
+        for (i, (provider, feed)) in feeds.iter().enumerate() {
+            if i > 0 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + slot_gap);
+            }
+            self.set_price_usd(*provider, feed, price, confidence)?;
+        }
+        Ok(())
+    }
+
+    /// Update a feed in USD through the given provider
+    pub(crate) fn set_price_usd(
+        &mut self,
+        provider: Provider,
+        feed: &Pubkey,
+        price: f64,
+        confidence: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.check_feeds(provider, &[*feed])?;
+        match provider {
+            Provider::Pyth => self.pyth().set_price_usd(feed, price, confidence),
+            Provider::Switchboard => self.switchboard().set_price_usd(feed, price, confidence),
+            Provider::Chainlink => self.chainlink().set_price_usd(feed, price, confidence),
+        }
+    }
+
+    /// Check that `provider` tracks every feed in `feeds`
+    ///
+    /// A feed tracked by a different provider gives `ProviderMismatch`. Feeds
+    /// tracked nowhere give `PriceFeedNotFound`, or `MultipleFeedsNotFound`
+    /// when more than one is missing.
+    fn check_feeds(&self, provider: Provider, feeds: &[Pubkey]) -> Result<(), ShadowOracleError> {
+        let mut missing = Vec::new();
+        for feed in feeds {
+            match self.provider_of(feed) {
+                Some(found) if found == provider => {}
+                Some(found) => {
+                    return Err(ShadowOracleError::ProviderMismatch {
+                        feed: self.feed_name(feed),
+                        expected: format!("{provider:?}"),
+                        found: format!("{found:?}"),
+                    })
+                }
+                None => missing.push(self.feed_name(feed)),
+            }
+        }
+        not_found(missing)
+    }
+
+    /// Provider tracking `feed`, checked in Pyth, Switchboard, Chainlink order
+    fn provider_of(&self, feed: &Pubkey) -> Option<Provider> {
+        if self.pyth.contains(feed) {
+            Some(Provider::Pyth)
+        } else if self.switchboard.contains(feed) {
+            Some(Provider::Switchboard)
+        } else if self.chainlink.contains(feed) {
+            Some(Provider::Chainlink)
+        } else {
+            None
+        }
+    }
+
+    /// Weighted USD price and confidence of a set of feeds
+    fn index_price(&self, constituents: &[(Pubkey, f64)]) -> Result<(f64, f64), ShadowOracleError> {
+        let mut price = 0.0;
+        let mut conf = 0.0;
+        let mut missing = Vec::new();
+        for (feed, weight) in constituents {
+            match self.price_usd(feed) {
+                Some((p, c)) => {
+                    price += weight * p;
+                    conf += weight.abs() * c;
+                }
+                None => missing.push(self.feed_name(feed)),
+            }
+        }
+        not_found(missing)?;
+        Ok((price, conf))
+    }
+
+    /// Name of a feed for error messages, using its label on any provider
+    fn feed_name(&self, feed: &Pubkey) -> String {
+        let label = self
+            .pyth
+            .label(feed)
+            .or_else(|| self.switchboard.label(feed))
+            .or_else(|| self.chainlink.label(feed));
+        providers::feed_name(feed, label)
+    }
+
+    /// USD price of a feed on the given provider
+    fn provider_price_usd(&self, provider: Provider, feed: &Pubkey) -> Option<(f64, f64)> {
+        match provider {
+            Provider::Pyth => self.pyth.price_usd(feed),
+            Provider::Switchboard => self.switchboard.price_usd(feed),
+            Provider::Chainlink => self.chainlink.price_usd(feed),
+        }
+    }
+
+    /// USD price of a feed on whichever provider tracks it
+    fn price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.pyth
+            .price_usd(feed)
+            .or_else(|| self.switchboard.price_usd(feed))
+            .or_else(|| self.chainlink.price_usd(feed))
+    }
+}
+
+/// Slot and timestamp byte ranges of a provider's feed accounts
+fn volatile_fields(provider: Provider) -> Vec<Range<usize>> {
+    match provider {
+        Provider::Pyth => providers::pyth::VOLATILE_FIELDS.to_vec(),
+        Provider::Switchboard => providers::switchboard::VOLATILE_FIELDS.to_vec(),
+        Provider::Chainlink => providers::chainlink::volatile_fields(),
+    }
+}
+
+/// Error for feeds that were looked up and not found, if any
+fn not_found(mut missing: Vec<String>) -> Result<(), ShadowOracleError> {
+    match missing.len() {
+        0 => Ok(()),
+        1 => Err(ShadowOracleError::PriceFeedNotFound(missing.remove(0))),
+        _ => Err(ShadowOracleError::MultipleFeedsNotFound(missing)),
     }
 }
 
@@ -101,6 +796,28 @@ pub mod feeds {
         pub fn usdt_usd() -> Pubkey {
             Pubkey::from_str("3vxLXJqLqF3JG5TCbYycbKWRBbCJQLxQmBGCkyqEEefL").unwrap()
         }
+
+        /// Symbols with a known address, as accepted by [`by_symbol`]
+        pub fn supported_symbols() -> &'static [&'static str] {
+            &["SOL/USD", "BTC/USD", "ETH/USD", "USDC/USD", "USDT/USD"]
+        }
+
+        /// Look up the USD feed address for `asset`
+        pub fn by_asset(asset: &crate::Asset) -> Option<Pubkey> {
+            by_symbol(&format!("{asset}/USD"))
+        }
+
+        /// Look up a feed address by symbol, e.g. `"SOL/USD"` (case-insensitive)
+        pub fn by_symbol(symbol: &str) -> Option<Pubkey> {
+            match symbol.to_ascii_uppercase().as_str() {
+                "SOL/USD" => Some(sol_usd()),
+                "BTC/USD" => Some(btc_usd()),
+                "ETH/USD" => Some(eth_usd()),
+                "USDC/USD" => Some(usdc_usd()),
+                "USDT/USD" => Some(usdt_usd()),
+                _ => None,
+            }
+        }
     }
 
     pub mod switchboard {
@@ -118,6 +835,26 @@ pub mod feeds {
         pub fn eth_usd() -> Pubkey {
             Pubkey::from_str("HNStfhaLnqwF2ZtJUizaA9uHDAVB976r2AgTUx9LrdEo").unwrap()
         }
+
+        /// Symbols with a known address, as accepted by [`by_symbol`]
+        pub fn supported_symbols() -> &'static [&'static str] {
+            &["SOL/USD", "BTC/USD", "ETH/USD"]
+        }
+
+        /// Look up the USD feed address for `asset`
+        pub fn by_asset(asset: &crate::Asset) -> Option<Pubkey> {
+            by_symbol(&format!("{asset}/USD"))
+        }
+
+        /// Look up a feed address by symbol, e.g. `"SOL/USD"` (case-insensitive)
+        pub fn by_symbol(symbol: &str) -> Option<Pubkey> {
+            match symbol.to_ascii_uppercase().as_str() {
+                "SOL/USD" => Some(sol_usd()),
+                "BTC/USD" => Some(btc_usd()),
+                "ETH/USD" => Some(eth_usd()),
+                _ => None,
+            }
+        }
     }
 
     pub mod chainlink {
@@ -135,5 +872,698 @@ pub mod feeds {
         pub fn eth_usd() -> Pubkey {
             Pubkey::from_str("5JcBbyiwxPxFMvNJHLxLqg5LPZeC4sC3VdWFfaKManYm").unwrap()
         }
+
+        /// Symbols with a known address, as accepted by [`by_symbol`]
+        pub fn supported_symbols() -> &'static [&'static str] {
+            &["SOL/USD", "BTC/USD", "ETH/USD"]
+        }
+
+        /// Look up the USD feed address for `asset`
+        pub fn by_asset(asset: &crate::Asset) -> Option<Pubkey> {
+            by_symbol(&format!("{asset}/USD"))
+        }
+
+        /// Look up a feed address by symbol, e.g. `"SOL/USD"` (case-insensitive)
+        pub fn by_symbol(symbol: &str) -> Option<Pubkey> {
+            match symbol.to_ascii_uppercase().as_str() {
+                "SOL/USD" => Some(sol_usd()),
+                "BTC/USD" => Some(btc_usd()),
+                "ETH/USD" => Some(eth_usd()),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_persists_across_handles() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feed = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        oracle.pyth().set_price_usd(&feed, 120.0, 0.2).unwrap();
+
+        let (price, _) = oracle.pyth().get_price_usd(&feed).unwrap();
+        assert!((price - 120.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_create_and_refresh_index_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let a = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(100.0, 1.0));
+        let b = oracle
+            .switchboard()
+            .create_price_feed(PriceConf::new_usd(50.0, 0.5));
+        let index = oracle
+            .create_index_feed(Provider::Chainlink, &[(a, 0.5), (b, 2.0)])
+            .unwrap();
+
+        let price = oracle.chainlink().get_price(&index).unwrap();
+        assert!((price - 150.0).abs() < 0.001);
+
+        oracle.switchboard().set_price_usd(&b, 60.0, 0.5).unwrap();
+        oracle.refresh_index(&index).unwrap();
+
+        let price = oracle.chainlink().get_price(&index).unwrap();
+        assert!((price - 170.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_index_confidence_is_weighted() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let a = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(100.0, 1.0));
+        let b = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(50.0, 0.5));
+        let index = oracle
+            .create_index_feed(Provider::Pyth, &[(a, 1.0), (b, -1.0)])
+            .unwrap();
+
+        let (price, conf) = oracle.pyth().get_price_usd(&index).unwrap();
+        assert!((price - 50.0).abs() < 0.001);
+        assert!((conf - 1.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_index_missing_constituent() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let missing = Pubkey::new_unique();
+        let result = oracle.create_index_feed(Provider::Pyth, &[(missing, 1.0)]);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+
+        assert!(oracle.refresh_index(&missing).is_err());
+    }
+
+    #[test]
+    fn test_standard_feeds_set_price_by_asset() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feeds = oracle.switchboard().create_standard_feeds();
+        assert_eq!(feeds.feed(Asset::Eth), Some(feeds.eth));
+
+        feeds
+            .set_price(&mut oracle, Provider::Switchboard, Asset::Sol, 60.0, 0.5)
+            .unwrap();
+
+        let (price, std_dev) = oracle.switchboard().get_price(&feeds.sol).unwrap();
+        assert!((price - 60.0).abs() < 0.001);
+        assert!((std_dev - 0.5).abs() < 0.001);
+
+        let (btc, _) = oracle.switchboard().get_price(&feeds.btc).unwrap();
+        assert!((btc - 43000.0).abs() < 0.001);
+
+        // Feeds were created on Switchboard, so Pyth does not know them
+        assert!(feeds
+            .set_price(&mut oracle, Provider::Pyth, Asset::Sol, 60.0, 0.5)
+            .is_err());
+
+        let other = Asset::Other("JUP".to_string());
+        assert_eq!(feeds.feed(other.clone()), None);
+        assert!(matches!(
+            feeds.set_price(&mut oracle, Provider::Switchboard, other, 1.0, 0.0),
+            Err(ShadowOracleError::PriceFeedNotFound(symbol)) if symbol == "JUP"
+        ));
+    }
+
+    #[test]
+    fn test_not_found_error_names_labeled_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feed = oracle
+            .pyth()
+            .create_price_feed_labeled("SOL/USD", PriceConf::new_usd(100.0, 0.1));
+
+        // The feed lives on Pyth, so updating it through Chainlink fails
+        let err = oracle
+            .set_price_usd(Provider::Chainlink, &feed, 90.0, 0.1)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Feed SOL/USD ({feed}) belongs to Pyth, not Chainlink")
+        );
+    }
+
+    #[test]
+    fn test_assert_parity() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let pyth = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let sb = oracle
+            .switchboard()
+            .create_price_feed(PriceConf::new_usd(100.004, 0.1));
+        let cl = oracle
+            .chainlink()
+            .create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let feeds = [
+            (Provider::Pyth, pyth),
+            (Provider::Switchboard, sb),
+            (Provider::Chainlink, cl),
+        ];
+
+        assert!(oracle.assert_parity(&feeds, 0.01).is_ok());
+
+        oracle.chainlink().set_price(&cl, 98.0).unwrap();
+        let err = oracle.assert_parity(&feeds, 0.01).unwrap_err();
+        assert!(err.contains("Pyth"));
+        assert!(err.contains("Chainlink"));
+        assert!(err.contains("$98"));
+
+        // A feed looked up on the wrong provider is reported as missing
+        let err = oracle
+            .assert_parity(&[(Provider::Switchboard, pyth)], 0.01)
+            .unwrap_err();
+        assert!(err.contains("not found"));
+    }
+
+    #[test]
+    fn test_simulate_risk_off() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feeds = oracle.pyth().create_standard_feeds();
+        oracle
+            .simulate_risk_off(Provider::Pyth, &feeds, 40.0)
+            .unwrap();
+
+        for (feed, before) in [
+            (feeds.sol, 100.0),
+            (feeds.btc, 43000.0),
+            (feeds.eth, 2200.0),
+        ] {
+            let (price, _) = oracle.pyth().get_price_usd(&feed).unwrap();
+            assert!((price - before * 0.6).abs() < 0.01);
+        }
+        for feed in [feeds.usdc, feeds.usdt] {
+            let (price, _) = oracle.pyth().get_price_usd(&feed).unwrap();
+            assert!((price - 1.0).abs() < 0.0001);
+            assert_eq!(oracle.pyth().update_count(&feed), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_feeds_by_symbol() {
+        assert_eq!(
+            feeds::pyth::by_symbol("SOL/USD"),
+            Some(feeds::pyth::sol_usd())
+        );
+        assert_eq!(
+            feeds::pyth::by_symbol("usdt/usd"),
+            Some(feeds::pyth::usdt_usd())
+        );
+        assert_eq!(
+            feeds::switchboard::by_symbol("BTC/USD"),
+            Some(feeds::switchboard::btc_usd())
+        );
+        assert_eq!(
+            feeds::chainlink::by_symbol("ETH/USD"),
+            Some(feeds::chainlink::eth_usd())
+        );
+        assert_eq!(feeds::chainlink::by_symbol("USDC/USD"), None);
+    }
+
+    #[test]
+    fn test_feeds_supported_symbols() {
+        for symbol in feeds::pyth::supported_symbols() {
+            assert!(feeds::pyth::by_symbol(symbol).is_some());
+        }
+        for symbol in feeds::switchboard::supported_symbols() {
+            assert!(feeds::switchboard::by_symbol(symbol).is_some());
+        }
+        for symbol in feeds::chainlink::supported_symbols() {
+            assert!(feeds::chainlink::by_symbol(symbol).is_some());
+        }
+        assert_eq!(feeds::pyth::supported_symbols().len(), 5);
+        assert!(!feeds::chainlink::supported_symbols().contains(&"USDC/USD"));
+    }
+
+    #[test]
+    fn test_feeds_by_asset() {
+        assert_eq!(
+            feeds::pyth::by_asset(&Asset::Usdc),
+            Some(feeds::pyth::usdc_usd())
+        );
+        assert_eq!(
+            feeds::switchboard::by_asset(&Asset::Eth),
+            Some(feeds::switchboard::eth_usd())
+        );
+        assert_eq!(feeds::chainlink::by_asset(&Asset::Usdt), None);
+        assert_eq!(
+            feeds::pyth::by_asset(&Asset::Other("DOGE".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mirror_mainnet_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feed = oracle
+            .mirror_mainnet_feed(Provider::Chainlink, "BTC/USD", 65000.0, 0.0)
+            .unwrap();
+        assert_eq!(feed, feeds::chainlink::btc_usd());
+
+        let price = oracle.chainlink().get_price(&feed).unwrap();
+        assert!((price - 65000.0).abs() < 0.001);
+        assert!(oracle.svm.get_account(&feed).is_some());
+
+        assert_eq!(
+            oracle.mirror_mainnet_feed(Provider::Switchboard, "DOGE/USD", 0.1, 0.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_feed_count() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        oracle.pyth().create_standard_feeds();
+        oracle
+            .chainlink()
+            .create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(oracle.feed_count(Provider::Pyth), 5);
+        assert_eq!(oracle.feed_count(Provider::Switchboard), 0);
+        assert_eq!(oracle.feed_count(Provider::Chainlink), 1);
+        assert_eq!(oracle.pyth().len(), 5);
+    }
+
+    #[test]
+    fn test_cross_provider_errors() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feeds = oracle.pyth().create_standard_feeds();
+        let result = oracle.simulate_risk_off(Provider::Switchboard, &feeds, 40.0);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::ProviderMismatch { ref expected, ref found, .. })
+                if expected == "Switchboard" && found == "Pyth"
+        ));
+
+        // Nothing was crashed
+        let (price, _) = oracle.pyth().get_price_usd(&feeds.sol).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let result =
+            oracle.create_index_feed(Provider::Pyth, &[(a, 1.0), (feeds.sol, 1.0), (b, 1.0)]);
+        match result {
+            Err(ShadowOracleError::MultipleFeedsNotFound(missing)) => {
+                assert_eq!(missing, vec![a.to_string(), b.to_string()]);
+            }
+            other => panic!("expected MultipleFeedsNotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_create_all_reads_back_equal_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let configs = [
+            PriceConf::new_usd(123.45, 0.5),
+            PriceConf::new_usd(0.29, 0.01),
+            PriceConf::stablecoin(),
+            PriceConf::new_usd(150.0, 0.1).with_expo(-6),
+            PriceConf::new_usd(0.0001234, 0.000001).with_expo(-10),
+        ];
+        for conf in configs {
+            let expected = conf.price_usd();
+            let (pyth, switchboard, chainlink) = oracle.create_all(conf);
+            let feeds = [
+                (Provider::Pyth, pyth),
+                (Provider::Switchboard, switchboard),
+                (Provider::Chainlink, chainlink),
+            ];
+            for (provider, feed) in feeds {
+                let (price, _) = oracle.provider_price_usd(provider, &feed).unwrap();
+                assert!(
+                    (price - expected).abs() <= expected.abs() * 1e-9,
+                    "{provider:?}: {price} != {expected}"
+                );
+            }
+            oracle.assert_parity(&feeds, 1e-9).unwrap();
+        }
+
+        // USD updates honor the feed exponent
+        let (pyth, _, _) = oracle.create_all(PriceConf::new_usd(150.0, 0.1).with_expo(-6));
+        oracle.pyth().set_price_usd(&pyth, 160.0, 0.2).unwrap();
+        assert_eq!(oracle.pyth().get_price(&pyth), Some((160_000_000, 200_000)));
+        let (price, conf) = oracle.pyth().get_price_usd(&pyth).unwrap();
+        assert!((price - 160.0).abs() < 1e-9);
+        assert!((conf - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_template_expo_keeps_usd_value() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle =
+            ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_expo(-6));
+
+        let (pyth, switchboard, chainlink) = oracle.create_all(PriceConf::new_usd(100.0, 0.1));
+        let feeds = [
+            (Provider::Pyth, pyth),
+            (Provider::Switchboard, switchboard),
+            (Provider::Chainlink, chainlink),
+        ];
+        for (provider, feed) in feeds {
+            let (price, _) = oracle.provider_price_usd(provider, &feed).unwrap();
+            assert!((price - 100.0).abs() < 1e-9, "{provider:?}: {price} != 100");
+        }
+        assert_eq!(oracle.pyth().get_price(&pyth), Some((100_000_000, 100_000)));
+    }
+
+    #[test]
+    fn test_template_keeps_explicit_default_expo() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle =
+            ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_expo(-6));
+
+        let conf = PriceConf::new_usd(100.0, 0.1).with_expo(-8);
+        let (pyth, switchboard, chainlink) = oracle.create_all(conf);
+        assert_eq!(
+            oracle.pyth().get_price(&pyth),
+            Some((10_000_000_000, 10_000_000))
+        );
+        assert_eq!(oracle.pyth().reading_as_conf(&pyth).unwrap().expo, -8);
+        assert_eq!(
+            oracle
+                .switchboard()
+                .reading_as_conf(&switchboard)
+                .unwrap()
+                .expo,
+            -8
+        );
+        assert_eq!(oracle.chainlink().get_decimals(&chainlink), Some(8));
+        assert_eq!(
+            oracle.chainlink().get_price_from_svm(&chainlink),
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn test_template_rescale_overflow_is_rejected() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle =
+            ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_expo(-18));
+
+        // $43,000 needs 4.3e22 at expo -18, past i64::MAX
+        let conf = PriceConf::new_usd(43_000.0, 10.0);
+        for result in [
+            oracle.pyth().try_create_price_feed(conf.clone()),
+            oracle.switchboard().try_create_price_feed(conf.clone()),
+            oracle.chainlink().try_create_price_feed(conf.clone()),
+        ] {
+            assert!(matches!(
+                result,
+                Err(ShadowOracleError::InvalidPriceData(_))
+            ));
+        }
+        assert!(oracle.all_feeds().is_empty());
+
+        // Small prices still fit
+        let feed = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(1.5, 0.01));
+        assert_eq!(
+            oracle.pyth().get_price(&feed),
+            Some((1_500_000_000_000_000_000, 10_000_000_000_000_000))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit at expo -18")]
+    fn test_create_price_feed_panics_on_template_overflow() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle =
+            ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_expo(-18));
+        oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(43_000.0, 10.0));
+    }
+
+    #[test]
+    fn test_all_feeds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+        assert!(oracle.all_feeds().is_empty());
+
+        let chainlink = oracle
+            .chainlink()
+            .create_price_feed(PriceConf::new_usd(3000.0, 0.0));
+        let feeds = oracle.pyth().create_standard_feeds();
+        let switchboard = oracle
+            .switchboard()
+            .create_price_feed(PriceConf::new_usd(42.0, 0.1));
+
+        let all = oracle.all_feeds();
+        assert_eq!(all.len(), 7);
+        assert_eq!(all, oracle.all_feeds());
+
+        let providers: Vec<Provider> = all.iter().map(|(provider, _, _)| *provider).collect();
+        assert!(providers.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(all[5], (Provider::Switchboard, switchboard, 42.0));
+        assert_eq!(all[6], (Provider::Chainlink, chainlink, 3000.0));
+        assert!(all.contains(&(Provider::Pyth, feeds.btc, 43000.0)));
+    }
+
+    #[test]
+    fn test_create_all_standard_feeds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feeds = oracle.create_all_standard_feeds();
+        for provider in [Provider::Pyth, Provider::Switchboard, Provider::Chainlink] {
+            assert_eq!(oracle.feed_count(provider), 5);
+        }
+
+        let sol = [Provider::Pyth, Provider::Switchboard, Provider::Chainlink].map(|provider| {
+            let feed = feeds.for_provider(provider).sol;
+            oracle.provider_price_usd(provider, &feed).unwrap().0
+        });
+        assert_eq!(sol, [100.0; 3]);
+        assert_eq!(
+            feeds.for_provider(Provider::Chainlink).btc,
+            feeds.chainlink.btc
+        );
+    }
+
+    #[test]
+    fn test_create_market() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feeds = oracle
+            .create_market(&[
+                (Provider::Pyth, Asset::Sol, 150.0, 0.3),
+                (Provider::Switchboard, Asset::Sol, 151.0, 0.5),
+                (Provider::Chainlink, "JUP".into(), 0.9, 0.0),
+            ])
+            .unwrap();
+
+        assert_eq!(feeds.len(), 3);
+        let sol = feeds[&(Provider::Switchboard, Asset::Sol)];
+        assert!((oracle.switchboard().get_price_usd(&sol).unwrap().0 - 151.0).abs() < 0.001);
+        let jup = feeds[&(Provider::Chainlink, Asset::Other("JUP".to_string()))];
+        assert_eq!(oracle.chainlink().label(&jup).unwrap(), "JUP");
+
+        // Every entry fails under a template with out-of-range decimals
+        let mut oracle =
+            ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_decimals(40));
+        let result = oracle.create_market(&[
+            (Provider::Pyth, Asset::Btc, 43000.0, 10.0),
+            (Provider::Chainlink, Asset::Eth, 2200.0, 0.0),
+        ]);
+        match result {
+            Err(ShadowOracleError::FeedCreationFailed(failures)) => {
+                assert_eq!(failures.len(), 2);
+                assert!(failures[0].starts_with("BTC on Pyth"));
+                assert!(failures[1].starts_with("ETH on Chainlink"));
+            }
+            other => panic!("expected FeedCreationFailed, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+        oracle.set_clock(1_700_000_000, 100);
+
+        let feed = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let exact = oracle.checkpoint(&feed);
+        let stable = oracle.checkpoint_stable(&feed);
+        assert_eq!(exact.feed(), feed);
+
+        // A benign transaction that does not touch the feed
+        let payer = Pubkey::new_unique();
+        oracle.svm().airdrop(&payer, 1_000_000_000).unwrap();
+        assert!(oracle.assert_unchanged(&exact));
+        assert!(oracle.assert_unchanged(&stable));
+
+        // Moving only the timestamps breaks the exact checkpoint alone
+        oracle.pyth().make_stale(&feed, 120).unwrap();
+        assert!(!oracle.assert_unchanged(&exact));
+        assert!(oracle.assert_unchanged(&stable));
+
+        // A foreign write to the price is caught by both
+        let mut account = oracle.svm().get_account(&feed).unwrap();
+        account.data = PriceConf::new_usd(50.0, 0.1).to_pyth_bytes();
+        oracle.svm().set_account(feed, account).unwrap();
+        assert!(!oracle.assert_unchanged(&stable));
+
+        // A missing account stays unchanged while it stays missing
+        let missing = oracle.checkpoint(&Pubkey::new_unique());
+        assert!(oracle.assert_unchanged(&missing));
+    }
+
+    #[test]
+    fn test_register_program() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let feed = oracle
+            .switchboard()
+            .create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let owner = oracle.svm().get_account(&feed).unwrap().owner;
+        assert!(oracle.svm().get_account(&owner).is_none());
+
+        oracle.register_program(&owner).unwrap();
+        let program = oracle.svm().get_account(&owner).unwrap();
+        assert!(program.executable);
+        assert!(!oracle.svm().get_account(&feed).unwrap().executable);
+
+        // Registering again keeps the existing executable account
+        oracle.register_program(&owner).unwrap();
+        assert_eq!(oracle.svm().get_account(&owner).unwrap(), program);
+    }
+
+    #[test]
+    fn test_set_price_staggered() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+        oracle.set_clock(1_700_000_000, 100);
+
+        let (pyth, switchboard, chainlink) = oracle.create_all(PriceConf::new_usd(100.0, 0.1));
+        oracle
+            .set_price_staggered(
+                &[
+                    (Provider::Pyth, pyth),
+                    (Provider::Switchboard, switchboard),
+                    (Provider::Chainlink, chainlink),
+                ],
+                90.0,
+                0.2,
+                5,
+            )
+            .unwrap();
+
+        assert_eq!(oracle.pyth().get_slot(&pyth), Some(100));
+        assert_eq!(oracle.switchboard().get_slot(&switchboard), Some(105));
+        assert_eq!(oracle.chainlink().get_slot(&chainlink), Some(110));
+        assert_eq!(oracle.svm().get_sysvar::<Clock>().slot, 110);
+        oracle
+            .assert_parity(
+                &[(Provider::Pyth, pyth), (Provider::Chainlink, chainlink)],
+                0.001,
+            )
+            .unwrap();
+
+        // A mismatched feed fails before anything is written
+        let result = oracle.set_price_staggered(
+            &[(Provider::Pyth, pyth), (Provider::Pyth, chainlink)],
+            80.0,
+            0.2,
+            5,
+        );
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::ProviderMismatch { .. })
+        ));
+        assert_eq!(oracle.pyth().get_price_usd(&pyth).unwrap().0, 90.0);
+    }
+
+    #[test]
+    fn test_spread_bps() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        let a = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(101.0, 0.1));
+        let b = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_quoted(100.0, 0.01, -3));
+        let zero = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_usd(0.0, 0.0));
+
+        let spread = oracle.spread_bps(Provider::Pyth, &a, &b).unwrap();
+        assert!((spread - 100.0).abs() < 1e-9);
+        let spread = oracle.spread_bps(Provider::Pyth, &b, &a).unwrap();
+        assert!((spread + 99.00990099).abs() < 1e-6);
+
+        assert_eq!(oracle.spread_bps(Provider::Pyth, &a, &zero), None);
+        assert_eq!(oracle.spread_bps(Provider::Switchboard, &a, &b), None);
+        assert_eq!(
+            oracle.spread_bps(Provider::Pyth, &a, &Pubkey::new_unique()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+
+        oracle.set_clock(1_700_000_000, 1_000);
+        let (pyth, switchboard, chainlink) = oracle.create_all(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(oracle.pyth().get_timestamp(&pyth), Some(1_700_000_000));
+        assert_eq!(
+            oracle.switchboard().get_timestamp(&switchboard),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            oracle.chainlink().get_timestamp(&chainlink),
+            Some(1_700_000_000)
+        );
+        assert_eq!(oracle.pyth().get_slot(&pyth), Some(1_000));
+        assert_eq!(oracle.switchboard().get_slot(&switchboard), Some(1_000));
+        assert_eq!(oracle.chainlink().get_slot(&chainlink), Some(1_000));
+
+        // Updates follow the clock too
+        oracle.set_clock(1_700_000_060, 1_150);
+        oracle.pyth().set_price_usd(&pyth, 101.0, 0.1).unwrap();
+        assert_eq!(oracle.pyth().get_timestamp(&pyth), Some(1_700_000_060));
+        assert_eq!(oracle.pyth().get_slot(&pyth), Some(1_150));
     }
 }
@@ -1,7 +1,12 @@
 //! Common price types shared across all oracle providers
 
+/// Largest `decimals` or `|expo|` a feed can use without overflowing an `i128`
+/// power of ten or losing `f64` precision when scaling
+pub const MAX_DECIMALS: u32 = 18;
+
 /// Price status values (compatible across providers)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PriceStatus {
     #[default]
     Trading,
@@ -15,6 +20,7 @@ pub enum PriceStatus {
 /// This is provider-agnostic and gets converted to the appropriate
 /// on-chain format by each provider.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PriceConf {
     /// Price value (scaled by 10^|expo|)
     pub price: i64,
@@ -32,6 +38,15 @@ pub struct PriceConf {
     pub status: PriceStatus,
     /// Number of decimals for the asset (used by some providers)
     pub decimals: u8,
+    /// Price tick in the quote currency that `set_price_usd` rounds to, if any
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tick_size: Option<f64>,
+    /// `expo` was chosen for this feed and overrides a provider template
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) explicit_expo: bool,
+    /// `decimals` was chosen for this feed and overrides a provider template
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) explicit_decimals: bool,
 }
 
 impl Default for PriceConf {
@@ -45,13 +60,49 @@ impl Default for PriceConf {
             publish_time: None,
             status: PriceStatus::Trading,
             decimals: 8,
+            tick_size: None,
+            explicit_expo: false,
+            explicit_decimals: false,
         }
     }
 }
 
 impl PriceConf {
+    /// Create a price config in any quote currency with the given exponent
+    ///
+    /// Providers do not track the quote currency, so this works for EUR, GBP
+    /// or any other quote. `decimals` follows the exponent. The `*_usd`
+    /// constructors and getters are the USD special case of this.
+    ///
+    /// # Example
+    /// ```
+    /// use shadow_oracle::PriceConf;
+    ///
+    /// // A EUR-quoted feed at 1.085 with a 0.0002 band, 6 decimals
+    /// let conf = PriceConf::new_quoted(1.085, 0.0002, -6);
+    /// assert_eq!(conf.price, 1_085_000);
+    /// assert_eq!(conf.decimals, 6);
+    /// assert_eq!(conf.price_value(), 1.085);
+    /// ```
+    pub fn new_quoted(price: f64, confidence: f64, expo: i32) -> Self {
+        let scale = 10f64.powi(-expo);
+        Self {
+            price: (price * scale).round() as i64,
+            conf: (confidence * scale).round() as u64,
+            expo,
+            decimals: (-expo).max(0) as u8,
+            explicit_expo: true,
+            explicit_decimals: true,
+            ..Default::default()
+        }
+    }
+
     /// Create a new price config with the given USD price
     ///
+    /// Same as [`new_quoted`](Self::new_quoted) with an exponent of -8,
+    /// except that a provider template may still change the exponent and
+    /// decimals.
+    ///
     /// # Example
     /// ```
     /// use shadow_oracle::PriceConf;
@@ -60,19 +111,51 @@ impl PriceConf {
     /// let conf = PriceConf::new_usd(100.50, 0.05);
     /// ```
     pub fn new_usd(price: f64, confidence: f64) -> Self {
-        let expo = -8i32;
-        let scale = 10f64.powi(expo.abs());
         Self {
-            price: (price * scale) as i64,
-            conf: (confidence * scale) as u64,
-            expo,
-            ..Default::default()
+            explicit_expo: false,
+            explicit_decimals: false,
+            ..Self::new_quoted(price, confidence, -8)
+        }
+    }
+
+    /// Create a USD price config whose EMA differs from the spot price
+    ///
+    /// # Example
+    /// ```
+    /// use shadow_oracle::PriceConf;
+    ///
+    /// // Spot $100 while the EMA still sits at $110
+    /// let conf = PriceConf::new_usd_with_ema(100.0, 0.05, 110.0, 0.08);
+    /// assert_eq!(conf.ema_price, Some(11_000_000_000));
+    /// ```
+    pub fn new_usd_with_ema(price: f64, confidence: f64, ema_price: f64, ema_conf: f64) -> Self {
+        let conf = Self::new_usd(price, confidence);
+        let scale = 10f64.powi(conf.expo.abs());
+        Self {
+            ema_price: Some((ema_price * scale).round() as i64),
+            ema_conf: Some((ema_conf * scale).round() as u64),
+            ..conf
         }
     }
 
     /// Create a stablecoin price (pegged to $1.00)
     pub fn stablecoin() -> Self {
-        Self::new_usd(1.0, 0.0001)
+        Self::stablecoin_with_conf(0.0001)
+    }
+
+    /// Create a stablecoin price (pegged to $1.00) with a custom confidence
+    ///
+    /// # Example
+    /// ```
+    /// use shadow_oracle::PriceConf;
+    ///
+    /// // $1.00 with a $0.002 band
+    /// let conf = PriceConf::stablecoin_with_conf(0.002);
+    /// assert_eq!(conf.price_usd(), 1.0);
+    /// assert_eq!(conf.conf_usd(), 0.002);
+    /// ```
+    pub fn stablecoin_with_conf(conf_usd: f64) -> Self {
+        Self::new_usd(1.0, conf_usd)
     }
 
     /// Create a price with high volatility (wide confidence interval)
@@ -80,15 +163,70 @@ impl PriceConf {
         Self::new_usd(price, price * 0.02) // 2% confidence
     }
 
+    /// Create a price from a market cap and circulating supply
+    ///
+    /// The price is `market_cap_usd / circulating_supply`, with a confidence
+    /// of `conf_ratio` times the price.
+    ///
+    /// # Panics
+    /// Panics if `circulating_supply` is not positive.
+    ///
+    /// # Example
+    /// ```
+    /// use shadow_oracle::PriceConf;
+    ///
+    /// // $60B cap over 400M tokens is $150 per token, with a 0.1% band
+    /// let conf = PriceConf::from_market_cap(60_000_000_000.0, 400_000_000.0, 0.001);
+    /// assert_eq!(conf.price_usd(), 150.0);
+    /// assert_eq!(conf.conf_usd(), 0.15);
+    /// ```
+    pub fn from_market_cap(market_cap_usd: f64, circulating_supply: f64, conf_ratio: f64) -> Self {
+        assert!(
+            circulating_supply > 0.0,
+            "circulating supply must be positive, got {circulating_supply}"
+        );
+        let price = market_cap_usd / circulating_supply;
+        Self::new_usd(price, price * conf_ratio)
+    }
+
     /// Set custom decimals
     pub fn with_decimals(mut self, decimals: u8) -> Self {
         self.decimals = decimals;
+        self.explicit_decimals = true;
         self
     }
 
     /// Set custom exponent
     pub fn with_expo(mut self, expo: i32) -> Self {
         self.expo = expo;
+        self.explicit_expo = true;
+        self
+    }
+
+    /// Quantize later price updates to multiples of `tick_usd`, like a real feed
+    ///
+    /// Each provider's `set_price_usd` rounds the price to the nearest tick
+    /// before scaling it by the exponent or decimals, so 100.017 with a 0.01
+    /// tick is stored as 100.02. The `set_price_usd_checked` variants return
+    /// `InvalidPriceData` for an off-tick price instead. The confidence and
+    /// this config's own price are left as given.
+    /// [`validate`](Self::validate) rejects a tick that is not positive and
+    /// finite.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, Pyth};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut pyth = Pyth::new(&mut svm);
+    ///
+    /// let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_tick_size(0.01));
+    /// pyth.set_price_usd(&feed, 100.017, 0.1).unwrap();
+    /// assert_eq!(pyth.get_price(&feed), Some((10_002_000_000, 10_000_000)));
+    /// ```
+    pub fn with_tick_size(mut self, tick_usd: f64) -> Self {
+        self.tick_size = Some(tick_usd);
         self
     }
 
@@ -120,16 +258,242 @@ impl PriceConf {
         self
     }
 
+    /// Fill in settings left at their defaults from a template
+    ///
+    /// `expo` and `decimals` are replaced by the template's values unless they
+    /// were set explicitly, through [`new_quoted`](Self::new_quoted),
+    /// [`with_expo`](Self::with_expo) or [`with_decimals`](Self::with_decimals),
+    /// or differ from `PriceConf::default()`. When the exponent
+    /// changes, the price, confidence and EMA fields are rescaled to it, so
+    /// `new_usd(100.0, 0.1)` still reads as $100 under an expo -6 template.
+    /// Returns `InvalidPriceData` if a rescaled value does not fit.
+    pub(crate) fn with_template(
+        mut self,
+        template: &PriceConf,
+    ) -> Result<Self, crate::ShadowOracleError> {
+        let defaults = PriceConf::default();
+        if !self.explicit_expo && self.expo == defaults.expo && template.expo != self.expo {
+            self.rescale(template.expo)?;
+        }
+        if !self.explicit_decimals && self.decimals == defaults.decimals {
+            self.decimals = template.decimals;
+        }
+        Ok(self)
+    }
+
+    /// Move to a new exponent, rounding the mantissas so their values are kept
+    ///
+    /// Returns `InvalidPriceData`, leaving `self` unchanged, if a rescaled
+    /// mantissa overflows its integer type.
+    fn rescale(&mut self, expo: i32) -> Result<(), crate::ShadowOracleError> {
+        let factor = 10f64.powi(self.expo - expo);
+        let overflow = |value: f64| {
+            crate::ShadowOracleError::InvalidPriceData(format!(
+                "{value} does not fit at expo {expo}"
+            ))
+        };
+        let price = |value: i64| {
+            let scaled = (value as f64 * factor).round();
+            // i64::MAX as f64 rounds up to 2^63, which is already out of range
+            if scaled.is_finite() && scaled >= i64::MIN as f64 && scaled < i64::MAX as f64 {
+                Ok(scaled as i64)
+            } else {
+                Err(overflow(value as f64 * 10f64.powi(self.expo)))
+            }
+        };
+        let conf = |value: u64| {
+            let scaled = (value as f64 * factor).round();
+            if scaled.is_finite() && scaled >= 0.0 && scaled < u64::MAX as f64 {
+                Ok(scaled as u64)
+            } else {
+                Err(overflow(value as f64 * 10f64.powi(self.expo)))
+            }
+        };
+
+        let rescaled = (
+            price(self.price)?,
+            conf(self.conf)?,
+            self.ema_price.map(price).transpose()?,
+            self.ema_conf.map(conf).transpose()?,
+        );
+        (self.price, self.conf, self.ema_price, self.ema_conf) = rescaled;
+        self.expo = expo;
+        Ok(())
+    }
+
+    /// Check that `decimals` and `expo` are within [`MAX_DECIMALS`] and that
+    /// any tick size is positive
+    ///
+    /// Providers run this when creating a feed and panic on failure.
+    ///
+    /// # Example
+    /// ```
+    /// use shadow_oracle::PriceConf;
+    ///
+    /// assert!(PriceConf::new_usd(1.0, 0.0).with_decimals(40).validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), crate::ShadowOracleError> {
+        if self.decimals as u32 > MAX_DECIMALS {
+            return Err(crate::ShadowOracleError::InvalidPriceData(format!(
+                "decimals {} exceeds the maximum of {MAX_DECIMALS}",
+                self.decimals
+            )));
+        }
+        if self.expo.unsigned_abs() > MAX_DECIMALS {
+            return Err(crate::ShadowOracleError::InvalidPriceData(format!(
+                "expo {} exceeds the maximum magnitude of {MAX_DECIMALS}",
+                self.expo
+            )));
+        }
+        if let Some(tick) = self.tick_size {
+            if !(tick.is_finite() && tick > 0.0) {
+                return Err(crate::ShadowOracleError::InvalidPriceData(format!(
+                    "tick size {tick} is not a positive number"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get price as f64 in the quote currency, scaled by the exponent
+    pub fn price_value(&self) -> f64 {
+        self.price as f64 * 10f64.powi(self.expo)
+    }
+
+    /// Get confidence as f64 in the quote currency, scaled by the exponent
+    pub fn conf_value(&self) -> f64 {
+        self.conf as f64 * 10f64.powi(self.expo)
+    }
+
     /// Get price as f64 USD value
     pub fn price_usd(&self) -> f64 {
-        let scale = 10f64.powi(self.expo.abs());
-        self.price as f64 / scale
+        self.price_value()
     }
 
     /// Get confidence as f64 USD value
     pub fn conf_usd(&self) -> f64 {
-        let scale = 10f64.powi(self.expo.abs());
-        self.conf as f64 / scale
+        self.conf_value()
+    }
+
+    /// Serialize as a Pyth V2 price account without an SVM
+    ///
+    /// Uses the same encoding the Pyth provider writes. Slots are zero and the
+    /// timestamp is `publish_time`, or zero when unset.
+    pub fn to_pyth_bytes(&self) -> Vec<u8> {
+        crate::providers::pyth::price_account_bytes(self, &Default::default())
+    }
+
+    /// Serialize as a Switchboard V2 aggregator account without an SVM
+    ///
+    /// Uses the same encoding the Switchboard provider writes. The round slot
+    /// is zero and its timestamp is `publish_time`, or zero when unset.
+    pub fn to_switchboard_bytes(&self) -> Vec<u8> {
+        crate::providers::switchboard::aggregator_bytes(self, &Default::default())
+    }
+
+    /// Serialize as a Chainlink transmissions account without an SVM
+    ///
+    /// Uses the same encoding the Chainlink provider writes. The round slot is
+    /// zero and its timestamp is `publish_time`, or zero when unset.
+    pub fn to_chainlink_bytes(&self) -> Vec<u8> {
+        crate::providers::chainlink::transmissions_bytes(self, &Default::default())
+    }
+}
+
+/// Provider-agnostic snapshot of a feed's current state
+///
+/// Returned by each provider's `reading` method. Fields a provider has no
+/// equivalent for are filled with neutral values: Chainlink reports a zero
+/// confidence, and Switchboard and Chainlink always report `Trading`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceReading {
+    /// Price in USD
+    pub price_usd: f64,
+    /// Confidence (Pyth) or std deviation (Switchboard) in USD
+    pub conf_usd: f64,
+    /// Exponent of the stored integer price
+    pub expo: i32,
+    /// Slot of the last update
+    pub slot: u64,
+    /// Unix timestamp of the last update
+    pub timestamp: i64,
+    /// Trading status
+    pub status: PriceStatus,
+}
+
+/// A price recorded by a provider's opt-in history log (see `with_history`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricePoint {
+    /// Slot the feed reported after the update
+    pub slot: u64,
+    /// Timestamp the feed reported after the update
+    pub timestamp: i64,
+    /// Price in USD
+    pub price_usd: f64,
+    /// Confidence or std deviation in USD (zero for Chainlink)
+    pub conf_usd: f64,
+}
+
+/// Round `value` to the nearest multiple of `tick`, or leave it as is without one
+pub(crate) fn round_to_tick(value: f64, tick: Option<f64>) -> f64 {
+    match tick {
+        Some(tick) => (value / tick).round() * tick,
+        None => value,
+    }
+}
+
+/// Reject negative or NaN confidence values before they are cast to unsigned
+pub(crate) fn check_confidence(confidence: f64) -> Result<(), crate::ShadowOracleError> {
+    if confidence.is_nan() || confidence < 0.0 {
+        return Err(crate::ShadowOracleError::InvalidPriceData(format!(
+            "confidence must be non-negative, got {confidence}"
+        )));
+    }
+    Ok(())
+}
+
+/// Reject values with more precision than `expo` can represent
+///
+/// A value fits when scaling it by `10^-expo` lands on an integer, up to `f64`
+/// rounding noise, so storing it loses nothing.
+pub(crate) fn check_fits_expo(value: f64, expo: i32) -> Result<(), crate::ShadowOracleError> {
+    let scaled = value * 10f64.powi(-expo);
+    let tolerance = (scaled.abs() * 16.0 * f64::EPSILON).max(1e-9);
+    if (scaled - scaled.round()).abs() > tolerance {
+        return Err(crate::ShadowOracleError::InvalidPriceData(format!(
+            "{value} has more precision than expo {expo} can represent"
+        )));
+    }
+    Ok(())
+}
+
+/// Reject a value off the `tick` grid; anything passes without a tick
+///
+/// Uses the same tolerance for `f64` rounding noise as [`check_fits_expo`].
+pub(crate) fn check_on_tick(value: f64, tick: Option<f64>) -> Result<(), crate::ShadowOracleError> {
+    let Some(tick) = tick else {
+        return Ok(());
+    };
+    let ticks = value / tick;
+    let tolerance = (ticks.abs() * 16.0 * f64::EPSILON).max(1e-9);
+    if (ticks - ticks.round()).abs() > tolerance {
+        return Err(crate::ShadowOracleError::InvalidPriceData(format!(
+            "{value} is not a multiple of the tick size {tick}"
+        )));
+    }
+    Ok(())
+}
+
+/// Build an exact decimal from an integer mantissa and base-10 exponent
+#[cfg(feature = "decimal")]
+pub(crate) fn decimal_from_mantissa(mantissa: i128, expo: i32) -> Option<rust_decimal::Decimal> {
+    use rust_decimal::Decimal;
+
+    if expo <= 0 {
+        Decimal::try_from_i128_with_scale(mantissa, expo.unsigned_abs()).ok()
+    } else {
+        let multiplier = 10i128.checked_pow(expo as u32)?;
+        Decimal::try_from_i128_with_scale(mantissa.checked_mul(multiplier)?, 0).ok()
     }
 }
 
@@ -143,6 +507,135 @@ pub struct StandardFeeds {
     pub usdt: solana_pubkey::Pubkey,
 }
 
+/// [`StandardFeeds`] on every provider, from
+/// [`ShadowOracle::create_all_standard_feeds`](crate::ShadowOracle::create_all_standard_feeds)
+#[derive(Debug, Clone)]
+pub struct AllStandardFeeds {
+    pub pyth: StandardFeeds,
+    pub switchboard: StandardFeeds,
+    pub chainlink: StandardFeeds,
+}
+
+impl AllStandardFeeds {
+    /// Get the standard feeds created on `provider`
+    pub fn for_provider(&self, provider: crate::Provider) -> &StandardFeeds {
+        match provider {
+            crate::Provider::Pyth => &self.pyth,
+            crate::Provider::Switchboard => &self.switchboard,
+            crate::Provider::Chainlink => &self.chainlink,
+        }
+    }
+}
+
+/// Asset identifiers for symbol-based lookups
+///
+/// The assets covered by [`StandardFeeds`] get their own variants; anything
+/// else goes in `Other`. Parses from and displays as the ticker, e.g. `"SOL"`;
+/// parsing also accepts a USD pair such as `"sol/usd"`.
+///
+/// # Example
+/// ```
+/// use shadow_oracle::Asset;
+///
+/// assert_eq!("SOL/USD".parse::<Asset>().unwrap(), Asset::Sol);
+/// assert_eq!("JUP".parse::<Asset>().unwrap(), Asset::Other("JUP".to_string()));
+/// assert_eq!(Asset::Usdc.to_string(), "USDC");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Asset {
+    Sol,
+    Btc,
+    Eth,
+    Usdc,
+    Usdt,
+    Other(String),
+}
+
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Asset::Sol => f.write_str("SOL"),
+            Asset::Btc => f.write_str("BTC"),
+            Asset::Eth => f.write_str("ETH"),
+            Asset::Usdc => f.write_str("USDC"),
+            Asset::Usdt => f.write_str("USDT"),
+            Asset::Other(symbol) => f.write_str(symbol),
+        }
+    }
+}
+
+impl std::str::FromStr for Asset {
+    type Err = std::convert::Infallible;
+
+    /// Parse a ticker or USD pair, case-insensitive; unknown symbols become `Other`
+    fn from_str(symbol: &str) -> Result<Self, Self::Err> {
+        let upper = symbol.to_ascii_uppercase();
+        let ticker = upper.strip_suffix("/USD").unwrap_or(&upper);
+        Ok(match ticker {
+            "SOL" => Asset::Sol,
+            "BTC" => Asset::Btc,
+            "ETH" => Asset::Eth,
+            "USDC" => Asset::Usdc,
+            "USDT" => Asset::Usdt,
+            _ => Asset::Other(symbol.to_string()),
+        })
+    }
+}
+
+impl From<&str> for Asset {
+    fn from(symbol: &str) -> Self {
+        let Ok(asset) = symbol.parse();
+        asset
+    }
+}
+
+impl StandardFeeds {
+    /// Get the feed address for `asset`, or `None` for [`Asset::Other`]
+    pub fn feed(&self, asset: Asset) -> Option<solana_pubkey::Pubkey> {
+        match asset {
+            Asset::Sol => Some(self.sol),
+            Asset::Btc => Some(self.btc),
+            Asset::Eth => Some(self.eth),
+            Asset::Usdc => Some(self.usdc),
+            Asset::Usdt => Some(self.usdt),
+            Asset::Other(_) => None,
+        }
+    }
+
+    /// Update the price of `asset` in USD
+    ///
+    /// `provider` must be the provider these feeds were created with.
+    /// Returns `PriceFeedNotFound` for [`Asset::Other`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{Asset, Provider, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    /// let feeds = oracle.pyth().create_standard_feeds();
+    ///
+    /// // Crash SOL 40%
+    /// feeds
+    ///     .set_price(&mut oracle, Provider::Pyth, Asset::Sol, 60.0, 0.5)
+    ///     .unwrap();
+    /// ```
+    pub fn set_price(
+        &self,
+        oracle: &mut crate::ShadowOracle,
+        provider: crate::Provider,
+        asset: Asset,
+        price: f64,
+        confidence: f64,
+    ) -> Result<(), crate::ShadowOracleError> {
+        let feed = self
+            .feed(asset.clone())
+            .ok_or_else(|| crate::ShadowOracleError::PriceFeedNotFound(asset.to_string()))?;
+        oracle.set_price_usd(provider, &feed, price, confidence)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +646,9 @@ mod tests {
         assert_eq!(conf.price, 10050000000);
         assert_eq!(conf.conf, 5000000);
         assert_eq!(conf.expo, -8);
+
+        // 0.29 * 1e8 is just below 29_000_000 in f64 and must round, not truncate
+        assert_eq!(PriceConf::new_usd(0.29, 0.01).price, 29_000_000);
     }
 
     #[test]
@@ -162,6 +658,38 @@ mod tests {
         assert!((conf.conf_usd() - 0.789).abs() < 0.0001);
     }
 
+    #[test]
+    fn test_asset_parse_and_display() {
+        for asset in [Asset::Sol, Asset::Btc, Asset::Eth, Asset::Usdc, Asset::Usdt] {
+            assert_eq!(asset.to_string().parse::<Asset>().unwrap(), asset);
+        }
+        assert_eq!(Asset::from("usdt/usd"), Asset::Usdt);
+        assert_eq!(Asset::from("Btc"), Asset::Btc);
+
+        let other = Asset::from("JUP/USD");
+        assert_eq!(other, Asset::Other("JUP/USD".to_string()));
+        assert_eq!(other.to_string(), "JUP/USD");
+    }
+
+    #[test]
+    fn test_new_quoted() {
+        let conf = PriceConf::new_quoted(0.8525, 0.0001, -5);
+        assert_eq!(conf.price, 85_250);
+        assert_eq!(conf.conf, 10);
+        assert_eq!(conf.expo, -5);
+        assert_eq!(conf.decimals, 5);
+        assert!((conf.price_value() - 0.8525).abs() < 1e-12);
+        assert!((conf.conf_value() - 0.0001).abs() < 1e-12);
+
+        let usd = PriceConf::new_usd(100.5, 0.05);
+        let quoted = PriceConf::new_quoted(100.5, 0.05, -8);
+        assert_eq!(
+            (usd.price, usd.conf, usd.expo),
+            (quoted.price, quoted.conf, quoted.expo)
+        );
+        assert_eq!(usd.decimals, quoted.decimals);
+    }
+
     #[test]
     fn test_stablecoin() {
         let conf = PriceConf::stablecoin();
@@ -180,4 +708,67 @@ mod tests {
         let conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, reference_time);
         assert_eq!(conf.publish_time, Some(700)); // 1000 - 300
     }
+
+    #[test]
+    fn test_with_template() {
+        let template = PriceConf::default().with_expo(-6).with_decimals(6);
+
+        let conf = PriceConf::new_usd_with_ema(100.0, 0.1, 110.0, 0.2)
+            .with_template(&template)
+            .unwrap();
+        assert_eq!(conf.expo, -6);
+        assert_eq!(conf.decimals, 6);
+        assert_eq!((conf.price, conf.conf), (100_000_000, 100_000));
+        assert_eq!(
+            (conf.ema_price, conf.ema_conf),
+            (Some(110_000_000), Some(200_000))
+        );
+
+        let explicit = PriceConf::new_usd(100.0, 0.1)
+            .with_expo(-4)
+            .with_decimals(9)
+            .with_template(&template)
+            .unwrap();
+        assert_eq!(explicit.expo, -4);
+        assert_eq!(explicit.decimals, 9);
+
+        // Explicit settings win even when they equal the defaults
+        let default_valued = PriceConf::new_usd(100.0, 0.1)
+            .with_expo(-8)
+            .with_decimals(8)
+            .with_template(&template)
+            .unwrap();
+        assert_eq!(default_valued.expo, -8);
+        assert_eq!(default_valued.decimals, 8);
+        assert_eq!(default_valued.price, 10_000_000_000);
+
+        let quoted = PriceConf::new_quoted(100.0, 0.1, -8)
+            .with_template(&template)
+            .unwrap();
+        assert_eq!((quoted.expo, quoted.decimals), (-8, 8));
+    }
+
+    #[test]
+    fn test_validate_bounds() {
+        assert!(PriceConf::new_usd(100.0, 0.1).validate().is_ok());
+        assert!(PriceConf::default()
+            .with_decimals(18)
+            .with_expo(-18)
+            .validate()
+            .is_ok());
+
+        assert!(matches!(
+            PriceConf::default().with_decimals(40).validate(),
+            Err(crate::ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(PriceConf::default().with_expo(-19).validate().is_err());
+        assert!(PriceConf::default().with_expo(19).validate().is_err());
+
+        assert!(PriceConf::default().with_tick_size(0.01).validate().is_ok());
+        assert!(PriceConf::default().with_tick_size(0.0).validate().is_err());
+        assert!(PriceConf::default()
+            .with_tick_size(f64::NAN)
+            .validate()
+            .is_err());
+    }
 }
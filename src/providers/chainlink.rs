@@ -3,14 +3,16 @@
 //! Mock Chainlink data feeds for LiteSVM testing.
 //! Based on the Chainlink Solana feeds program.
 
-use crate::{PriceConf, ShadowOracleError, StandardFeeds};
+use super::{ProviderState, SplitMix64};
+use crate::{PriceConf, PricePoint, PriceReading, PriceStatus, ShadowOracleError, StandardFeeds};
 use litesvm::LiteSVM;
 use solana_account::Account;
 use solana_clock::Clock;
 use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 use solana_signer::Signer;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
 use std::str::FromStr;
 
 /// Chainlink Solana Program ID (mainnet)
@@ -19,6 +21,44 @@ pub const CHAINLINK_PROGRAM_ID: &str = "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJ
 /// Chainlink Store Program ID
 pub const CHAINLINK_STORE_PROGRAM_ID: &str = "CaH12fwNTKJAG8PxEvo9R96Zc2j8Jq3Q5K9B7tTFQ2by";
 
+/// Version byte of the transmissions account header
+const VERSION: u8 = 1;
+/// Seconds the latest round may lag the clock before it counts as stale
+const MAX_ROUND_AGE_SECS: i64 = 60;
+/// Size of the account header preceding the transmissions ring buffer
+const HEADER_SIZE: usize = 192;
+/// Size of a single transmission in the ring buffer
+const TRANSMISSION_SIZE: usize = 48;
+/// Number of transmissions retained in the ring buffer
+const NUM_TRANSMISSIONS: usize = 16;
+/// Header `state` of an initialized feed
+const STATE_NORMAL: u8 = 1;
+/// Header `state` of a flagged feed
+const STATE_FLAGGED: u8 = 2;
+/// Units of `flagging_threshold` per 100% deviation (1000 is 1%)
+const THRESHOLD_MULTIPLIER: f64 = 100_000.0;
+
+/// Slot and timestamp of every transmission, which differ between runs
+pub(crate) fn volatile_fields() -> Vec<Range<usize>> {
+    (0..NUM_TRANSMISSIONS)
+        .map(|i| {
+            let offset = HEADER_SIZE + i * TRANSMISSION_SIZE;
+            offset..offset + 12
+        })
+        .collect()
+}
+
+/// A single round in the transmissions ring buffer
+#[derive(Debug, Clone)]
+struct Transmission {
+    round_id: u32,
+    price: f64,
+    slot: u64,
+    timestamp: u32,
+    observations_count: u8,
+    observer_count: u8,
+}
+
 /// Chainlink feed data - manually serialized
 #[derive(Debug, Clone)]
 struct ChainlinkFeed {
@@ -27,10 +67,36 @@ struct ChainlinkFeed {
     slot: u64,
     timestamp: u32,
     round_id: u32,
+    /// Program that owns the feed account
+    owner: Pubkey,
+    /// Number of price updates applied since creation
+    update_count: u64,
+    /// Slot at which the feed was created
+    created_slot: u64,
+    /// Slot of the last `set_price` call, or the creation slot if never updated
+    last_update_slot: u64,
+    /// Config the feed was created from, after applying the provider template
+    config: PriceConf,
+    /// Optional name shown by `describe` and in error messages
+    label: Option<String>,
+    /// Prices recorded while history is enabled, oldest first
+    price_log: Vec<PricePoint>,
+    observations_count: u8,
+    observer_count: u8,
+    /// Deviation threshold written to the header
+    flagging_threshold: u32,
+    /// Whether the header marks the feed as flagged
+    flagged: bool,
+    /// Earlier rounds still retained in the ring buffer, oldest first
+    history: VecDeque<Transmission>,
+    /// Whether the account holds exactly the rent-exempt minimum instead of 1 SOL
+    rent_exempt: bool,
+    /// Price and flagged state saved by `capture_baseline`
+    baseline: Option<(f64, bool)>,
 }
 
 impl ChainlinkFeed {
-    fn from_conf(conf: &PriceConf, clock: &Clock) -> Self {
+    fn from_conf(conf: &PriceConf, clock: &Clock, owner: Pubkey) -> Self {
         let now = conf.publish_time.unwrap_or(clock.unix_timestamp);
 
         Self {
@@ -39,37 +105,87 @@ impl ChainlinkFeed {
             slot: clock.slot,
             timestamp: now as u32,
             round_id: 1,
+            owner,
+            update_count: 0,
+            created_slot: clock.slot,
+            last_update_slot: clock.slot,
+            config: conf.clone(),
+            label: None,
+            price_log: Vec::new(),
+            observations_count: 3,
+            observer_count: 3,
+            flagging_threshold: 1000,
+            flagged: false,
+            history: VecDeque::new(),
+            rent_exempt: false,
+            baseline: None,
         }
     }
 
     fn set_price(&mut self, price: f64, clock: &Clock) {
+        if self.history.len() == NUM_TRANSMISSIONS - 1 {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.transmission());
+
         self.price = price;
         self.slot = clock.slot;
         self.round_id += 1;
+        self.update_count += 1;
         self.timestamp = clock.unix_timestamp as u32;
     }
 
+    /// Update the latest result in place, keeping its slot, timestamp and round
+    fn overwrite_price(&mut self, price: f64) {
+        self.price = price;
+        self.update_count += 1;
+    }
+
     fn get_answer(&self) -> i128 {
+        self.answer_for(self.price)
+    }
+
+    fn answer_for(&self, price: f64) -> i128 {
         let scale = 10i128.pow(self.decimals as u32);
-        (self.price * scale as f64) as i128
+        (price * scale as f64).round() as i128
+    }
+
+    /// The latest round as a ring buffer entry
+    fn transmission(&self) -> Transmission {
+        Transmission {
+            round_id: self.round_id,
+            price: self.price,
+            slot: self.slot,
+            timestamp: self.timestamp,
+            observations_count: self.observations_count,
+            observer_count: self.observer_count,
+        }
+    }
+
+    /// All retained rounds, oldest first, ending with the latest round
+    fn transmissions(&self) -> Vec<Transmission> {
+        let mut rounds: Vec<Transmission> = self.history.iter().cloned().collect();
+        rounds.push(self.transmission());
+        rounds
     }
 
     /// Serialize to Chainlink-compatible format
     fn to_bytes(&self) -> Vec<u8> {
         // Simplified Chainlink feed account structure
         // Based on chainlink-solana transmissions account
-        const HEADER_SIZE: usize = 192;
-        const TRANSMISSION_SIZE: usize = 48;
-        const NUM_TRANSMISSIONS: usize = 16;
         let account_size = HEADER_SIZE + (TRANSMISSION_SIZE * NUM_TRANSMISSIONS);
 
         let mut data = vec![0u8; account_size];
 
         // Header
         // version (1 byte)
-        data[0] = 1;
-        // state (1 byte) - 1 = initialized
-        data[1] = 1;
+        data[0] = VERSION;
+        // state (1 byte) - 1 = initialized, 2 = flagged
+        data[1] = if self.flagged {
+            STATE_FLAGGED
+        } else {
+            STATE_NORMAL
+        };
 
         // owner (32 bytes) at offset 2
         // proposed_owner (32 bytes) at offset 34
@@ -80,7 +196,7 @@ impl ChainlinkFeed {
         data[130] = self.decimals;
 
         // flagging_threshold (4 bytes) at offset 131
-        data[131..135].copy_from_slice(&1000u32.to_le_bytes());
+        data[131..135].copy_from_slice(&self.flagging_threshold.to_le_bytes());
 
         // latest_round_id (4 bytes) at offset 135
         data[135..139].copy_from_slice(&self.round_id.to_le_bytes());
@@ -95,40 +211,156 @@ impl ChainlinkFeed {
         let cursor = (self.round_id - 1) % NUM_TRANSMISSIONS as u32;
         data[150..154].copy_from_slice(&cursor.to_le_bytes());
 
-        // Transmissions start at offset HEADER_SIZE
+        // Transmissions start at offset HEADER_SIZE, one per retained round
         // Each transmission: slot (8), timestamp (4), padding (4), answer (16), obs_count (1), observer_count (1), padding (14)
-        let tx_offset = HEADER_SIZE + (cursor as usize * TRANSMISSION_SIZE);
-
-        // slot
-        data[tx_offset..tx_offset + 8].copy_from_slice(&self.slot.to_le_bytes());
-        // timestamp
-        data[tx_offset + 8..tx_offset + 12].copy_from_slice(&self.timestamp.to_le_bytes());
-        // answer (i128)
-        let answer = self.get_answer();
-        data[tx_offset + 16..tx_offset + 32].copy_from_slice(&answer.to_le_bytes());
-        // observations_count
-        data[tx_offset + 32] = 3;
-        // observer_count
-        data[tx_offset + 33] = 3;
+        for transmission in self.transmissions() {
+            let cursor = (transmission.round_id - 1) % NUM_TRANSMISSIONS as u32;
+            let tx_offset = HEADER_SIZE + (cursor as usize * TRANSMISSION_SIZE);
+
+            // slot
+            data[tx_offset..tx_offset + 8].copy_from_slice(&transmission.slot.to_le_bytes());
+            // timestamp
+            data[tx_offset + 8..tx_offset + 12]
+                .copy_from_slice(&transmission.timestamp.to_le_bytes());
+            // answer (i128)
+            let answer = self.answer_for(transmission.price);
+            data[tx_offset + 16..tx_offset + 32].copy_from_slice(&answer.to_le_bytes());
+            // observations_count
+            data[tx_offset + 32] = transmission.observations_count;
+            // observer_count
+            data[tx_offset + 33] = transmission.observer_count;
+        }
 
         data
     }
+
+    /// Read the latest answer back from serialized account data, in USD
+    fn read_latest_answer(data: &[u8]) -> Option<f64> {
+        let decimals = *data.get(130)?;
+        let cursor = u32::from_le_bytes(data.get(150..154)?.try_into().ok()?) as usize;
+        let tx_offset = HEADER_SIZE + cursor * TRANSMISSION_SIZE;
+        let answer =
+            i128::from_le_bytes(data.get(tx_offset + 16..tx_offset + 32)?.try_into().ok()?);
+        Some(answer as f64 / 10f64.powi(decimals as i32))
+    }
+
+    /// Read `(live_cursor, live_length, latest_round_id)` from serialized account data
+    fn read_ring_state(data: &[u8]) -> Option<(u32, u32, u32)> {
+        let read_u32 = |offset: usize| {
+            Some(u32::from_le_bytes(
+                data.get(offset..offset + 4)?.try_into().ok()?,
+            ))
+        };
+        Some((read_u32(150)?, read_u32(142)?, read_u32(135)?))
+    }
+
+    /// Append the current price to the history log
+    fn record_price(&mut self) {
+        self.price_log.push(PricePoint {
+            slot: self.slot,
+            timestamp: self.timestamp as i64,
+            price_usd: self.price,
+            conf_usd: 0.0,
+        });
+    }
+}
+
+/// Transmissions account bytes for a config at the given clock, as a new feed
+/// writes them
+pub(crate) fn transmissions_bytes(conf: &PriceConf, clock: &Clock) -> Vec<u8> {
+    ChainlinkFeed::from_conf(conf, clock, Pubkey::default()).to_bytes()
+}
+
+/// Feeds and settings of a provider, kept across handles by [`crate::ShadowOracle`]
+#[derive(Default)]
+pub(crate) struct ChainlinkState {
+    price_feeds: HashMap<Pubkey, ChainlinkFeed>,
+    record_history: bool,
+    slot_frozen: bool,
+}
+
+impl ChainlinkState {
+    /// Whether `feed` is tracked
+    pub(crate) fn contains(&self, feed: &Pubkey) -> bool {
+        self.price_feeds.contains_key(feed)
+    }
+
+    /// Number of tracked feeds
+    pub(crate) fn len(&self) -> usize {
+        self.price_feeds.len()
+    }
+
+    /// Addresses of all tracked feeds, in no particular order
+    pub(crate) fn feeds(&self) -> impl Iterator<Item = &Pubkey> {
+        self.price_feeds.keys()
+    }
+
+    /// Label attached to a feed at creation, if any
+    pub(crate) fn label(&self, feed: &Pubkey) -> Option<&str> {
+        self.price_feeds.get(feed)?.label.as_deref()
+    }
+
+    /// Tick size the feed's config quantizes USD updates to, if any
+    pub(crate) fn tick_size(&self, feed: &Pubkey) -> Option<f64> {
+        self.price_feeds.get(feed)?.config.tick_size
+    }
+
+    /// Replace a tracked feed's label; does nothing for untracked feeds
+    pub(crate) fn set_label(&mut self, feed: &Pubkey, label: Option<String>) {
+        if let Some(state) = self.price_feeds.get_mut(feed) {
+            state.label = label;
+        }
+    }
+
+    /// Current price of a feed in USD, with a zero confidence
+    pub(crate) fn price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.price_feeds.get(feed).map(|a| (a.price, 0.0))
+    }
+
+    /// A feed's current state as a `PriceConf`, see [`Chainlink::reading_as_conf`]
+    pub(crate) fn reading_as_conf(&self, feed: &Pubkey) -> Option<PriceConf> {
+        let account = self.price_feeds.get(feed)?;
+        let scale = 10f64.powi(account.decimals as i32);
+        Some(PriceConf {
+            price: (account.price * scale).round() as i64,
+            conf: 0,
+            expo: -(account.decimals as i32),
+            publish_time: Some(account.timestamp as i64),
+            decimals: account.decimals,
+            tick_size: account.config.tick_size,
+            explicit_expo: true,
+            explicit_decimals: true,
+            ..Default::default()
+        })
+    }
 }
 
 /// Chainlink oracle provider for LiteSVM
 pub struct Chainlink<'a> {
     svm: &'a mut LiteSVM,
-    price_feeds: HashMap<Pubkey, ChainlinkFeed>,
+    state: ProviderState<'a, ChainlinkState>,
     program_id: Pubkey,
+    template: PriceConf,
 }
 
 impl<'a> Chainlink<'a> {
+    /// The Chainlink program ID ([`CHAINLINK_PROGRAM_ID`]) as a `Pubkey`
+    pub fn program_id_default() -> Pubkey {
+        Pubkey::from_str(CHAINLINK_PROGRAM_ID).unwrap()
+    }
+
+    /// Version byte the mock writes at the start of every feed account
+    pub fn version() -> u8 {
+        VERSION
+    }
+
     /// Create a new Chainlink provider
     pub fn new(svm: &'a mut LiteSVM) -> Self {
         Self {
             svm,
-            price_feeds: HashMap::new(),
-            program_id: Pubkey::from_str(CHAINLINK_PROGRAM_ID).unwrap(),
+            state: ProviderState::Owned(ChainlinkState::default()),
+            program_id: Self::program_id_default(),
+            template: PriceConf::default(),
         }
     }
 
@@ -136,310 +368,2136 @@ impl<'a> Chainlink<'a> {
     pub fn with_program_id(svm: &'a mut LiteSVM, program_id: Pubkey) -> Self {
         Self {
             svm,
-            price_feeds: HashMap::new(),
+            state: ProviderState::Owned(ChainlinkState::default()),
             program_id,
+            template: PriceConf::default(),
         }
     }
 
+    /// Create a provider operating on state owned elsewhere
+    pub(crate) fn from_state(svm: &'a mut LiteSVM, state: &'a mut ChainlinkState) -> Self {
+        Self {
+            svm,
+            state: ProviderState::Borrowed(state),
+            program_id: Self::program_id_default(),
+            template: PriceConf::default(),
+        }
+    }
+
+    /// Use `template` for the exponent and decimals of new feeds
+    ///
+    /// Applies to feeds whose `PriceConf` does not set `expo`/`decimals`
+    /// explicitly, such as [`PriceConf::new_usd`]; per-feed settings from
+    /// `new_quoted`, `with_expo` or `with_decimals` still win.
+    pub fn with_template(mut self, template: PriceConf) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Record every price set on this provider's feeds, see [`history`](Self::history)
+    ///
+    /// Off by default to avoid unbounded memory growth in long scenarios.
+    pub fn with_history(mut self) -> Self {
+        self.state.record_history = true;
+        self
+    }
+
+    /// Freeze or unfreeze slot and round advancement on price updates
+    ///
+    /// While frozen, `set_price` (and everything built on it) rewrites the
+    /// latest round in place instead of opening a new one, simulating several
+    /// updates within the same slot. Unfrozen (the default) every update
+    /// advances the round and takes the SVM clock's slot.
+    pub fn freeze_slot(&mut self, frozen: bool) {
+        self.state.slot_frozen = frozen;
+    }
+
     /// Create a new price feed account
     pub fn create_price_feed(&mut self, conf: PriceConf) -> Pubkey {
         let keypair = Keypair::new();
-        let pubkey = keypair.pubkey();
+        self.create_feed(keypair.pubkey(), conf, self.program_id)
+    }
 
-        let clock = self.svm.get_sysvar::<Clock>();
-        let feed = ChainlinkFeed::from_conf(&conf, &clock);
-        self.set_account(&pubkey, &feed);
-        self.price_feeds.insert(pubkey, feed);
+    /// Create a new price feed account, returning an error instead of panicking
+    ///
+    /// Fails with `InvalidPriceData` for an invalid config and `SvmError` when
+    /// the SVM rejects the account.
+    pub fn try_create_price_feed(&mut self, conf: PriceConf) -> Result<Pubkey, ShadowOracleError> {
+        let keypair = Keypair::new();
+        self.try_create_feed(keypair.pubkey(), conf, self.program_id)
+    }
 
-        pubkey
+    /// Create a price feed at a specific address, returning an error instead of panicking
+    ///
+    /// See [`try_create_price_feed`](Self::try_create_price_feed). Unlike
+    /// [`create_price_feed_at`](Self::create_price_feed_at), this refuses an
+    /// address that already holds a tracked feed with `FeedAlreadyExists`.
+    pub fn try_create_price_feed_at(
+        &mut self,
+        address: Pubkey,
+        conf: PriceConf,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        if self.state.contains(&address) {
+            return Err(ShadowOracleError::FeedAlreadyExists(address.to_string()));
+        }
+        self.try_create_feed(address, conf, self.program_id)
     }
 
     /// Create a price feed at a specific address
+    ///
+    /// If the address already holds a tracked feed, that feed is replaced: its
+    /// account is rewritten from `conf` and its metadata and history start
+    /// over. Two feeds never share one address.
     pub fn create_price_feed_at(&mut self, address: Pubkey, conf: PriceConf) -> Pubkey {
-        let clock = self.svm.get_sysvar::<Clock>();
-        let feed = ChainlinkFeed::from_conf(&conf, &clock);
-        self.set_account(&address, &feed);
-        self.price_feeds.insert(address, feed);
-        address
+        self.create_feed(address, conf, self.program_id)
+    }
+
+    /// Create a new price feed account with a label
+    ///
+    /// The label is shown by [`describe`](Self::describe) and in
+    /// `PriceFeedNotFound` errors raised through [`crate::ShadowOracle`].
+    pub fn create_price_feed_labeled(&mut self, label: &str, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        self.state.set_label(&feed, Some(label.to_string()));
+        feed
+    }
+
+    /// Create a new labeled price feed, returning an error instead of panicking
+    ///
+    /// See [`try_create_price_feed`](Self::try_create_price_feed).
+    pub fn try_create_price_feed_labeled(
+        &mut self,
+        label: &str,
+        conf: PriceConf,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let feed = self.try_create_price_feed(conf)?;
+        self.state.set_label(&feed, Some(label.to_string()));
+        Ok(feed)
+    }
+
+    /// Create a new price feed funded with exactly the rent-exempt minimum
+    ///
+    /// The balance is `Rent::minimum_balance(data_len)` from the SVM's rent
+    /// sysvar, as a program creating the account would fund it, rather than
+    /// the 1 SOL other constructors deposit. Later updates keep the balance.
+    pub fn create_price_feed_rent_exempt(&mut self, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        let state = self.state.price_feeds.get_mut(&feed).unwrap();
+        state.rent_exempt = true;
+        let state_clone = state.clone();
+        self.set_account(&feed, &state_clone)
+            .expect("Failed to set account");
+        feed
+    }
+
+    /// Create a new price feed account owned by `owner` instead of the provider's program
+    ///
+    /// Useful when feeds in one test belong to different programs, e.g. an
+    /// oracle adapter loaded into the SVM.
+    pub fn create_price_feed_owned_by(&mut self, owner: Pubkey, conf: PriceConf) -> Pubkey {
+        let keypair = Keypair::new();
+        self.create_feed(keypair.pubkey(), conf, owner)
+    }
+
+    /// Create a price feed at the PDA derived from `seeds` and `program_id`
+    ///
+    /// Returns the derived address together with its bump seed.
+    pub fn create_price_feed_at_pda(
+        &mut self,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        conf: PriceConf,
+    ) -> (Pubkey, u8) {
+        let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+        self.create_price_feed_at(address, conf);
+        (address, bump)
     }
 
     /// Update the price of an existing feed
     pub fn set_price(&mut self, feed: &Pubkey, price: f64) -> Result<(), ShadowOracleError> {
         let clock = self.svm.get_sysvar::<Clock>();
+        let slot_frozen = self.state.slot_frozen;
+        let record_history = self.state.record_history;
         let account = self
+            .state
             .price_feeds
             .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        account.set_price(price, &clock);
+        if slot_frozen {
+            account.overwrite_price(price);
+        } else {
+            account.set_price(price, &clock);
+        }
+        account.last_update_slot = clock.slot;
+        if record_history {
+            account.record_price();
+        }
         let account_clone = account.clone();
-        self.set_account(feed, &account_clone);
-        Ok(())
+        self.set_account(feed, &account_clone)
     }
 
     /// Alias for set_price with USD naming convention (Chainlink doesn't have confidence)
+    ///
+    /// The confidence is otherwise ignored, but a negative value is still
+    /// rejected with `InvalidPriceData` for consistency with the other providers.
+    /// The account stores the answer rounded to the feed's decimals, so
+    /// digits beyond them are silently dropped; see
+    /// [`set_price_usd_checked`](Self::set_price_usd_checked). A feed created
+    /// with [`PriceConf::with_tick_size`] first rounds the price to its tick.
     pub fn set_price_usd(
         &mut self,
         feed: &Pubkey,
         price: f64,
-        _confidence: f64, // ignored, Chainlink doesn't use confidence
+        confidence: f64,
     ) -> Result<(), ShadowOracleError> {
+        crate::price::check_confidence(confidence)?;
+        let price = crate::price::round_to_tick(price, self.state.tick_size(feed));
         self.set_price(feed, price)
     }
 
+    /// Like [`set_price_usd`](Self::set_price_usd), but returns
+    /// `InvalidPriceData` when the price has more precision than the feed's
+    /// decimals can represent, or is off the tick set by
+    /// [`PriceConf::with_tick_size`]
+    pub fn set_price_usd_checked(
+        &mut self,
+        feed: &Pubkey,
+        price: f64,
+        confidence: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let decimals = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .decimals;
+        crate::price::check_fits_expo(price, -(decimals as i32))?;
+        crate::price::check_on_tick(price, self.state.tick_size(feed))?;
+        self.set_price_usd(feed, price, confidence)
+    }
+
+    /// Get the number of feeds this provider tracks
+    pub fn len(&self) -> usize {
+        self.state.price_feeds.len()
+    }
+
+    /// Check whether this provider tracks no feeds
+    pub fn is_empty(&self) -> bool {
+        self.state.price_feeds.is_empty()
+    }
+
+    /// Check whether this provider tracks `feed`
+    pub fn contains(&self, feed: &Pubkey) -> bool {
+        self.state.price_feeds.contains_key(feed)
+    }
+
     /// Get the current price from a feed
     pub fn get_price(&self, feed: &Pubkey) -> Option<f64> {
-        self.price_feeds.get(feed).map(|a| a.price)
+        self.state.price_feeds.get(feed).map(|a| a.price)
     }
 
-    /// Get price in USD format (returns (price, 0.0) for API compatibility)
-    pub fn get_price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
-        self.get_price(feed).map(|p| (p, 0.0))
+    /// Get the latest answer in USD from the account stored in the SVM
+    ///
+    /// Unlike [`get_price`](Self::get_price), this deserializes the account a
+    /// program would read rather than the provider's cache. Like a program's
+    /// owner check, it returns `None` when the account is not owned by this
+    /// provider's program ID, including feeds created with
+    /// [`create_price_feed_owned_by`](Self::create_price_feed_owned_by).
+    pub fn get_price_from_svm(&self, feed: &Pubkey) -> Option<f64> {
+        let account = self.svm.get_account(feed)?;
+        if account.owner != self.program_id {
+            return None;
+        }
+        ChainlinkFeed::read_latest_answer(&account.data)
     }
 
-    /// Get the raw answer (scaled integer)
-    pub fn get_latest_answer(&self, feed: &Pubkey) -> Option<i128> {
-        self.price_feeds.get(feed).map(|a| a.get_answer())
+    /// Get price in USD format (returns (price, 0.0) for API compatibility)
+    pub fn get_price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.state.price_usd(feed)
     }
 
-    /// Get decimals for a feed
-    pub fn get_decimals(&self, feed: &Pubkey) -> Option<u8> {
-        self.price_feeds.get(feed).map(|a| a.decimals)
+    /// Get the confidence of a feed in USD, always zero for tracked feeds
+    ///
+    /// The same accessor exists on every provider: Pyth reports its aggregate
+    /// confidence, Switchboard its std deviation and Chainlink zero, since
+    /// its rounds carry no confidence.
+    pub fn conf_usd(&self, feed: &Pubkey) -> Option<f64> {
+        self.state.price_usd(feed).map(|(_, conf)| conf)
     }
 
-    /// Get the latest round ID
-    pub fn get_latest_round(&self, feed: &Pubkey) -> Option<u32> {
-        self.price_feeds.get(feed).map(|a| a.round_id)
+    /// Get the raw answer (scaled integer)
+    pub fn get_latest_answer(&self, feed: &Pubkey) -> Option<i128> {
+        self.state.price_feeds.get(feed).map(|a| a.get_answer())
     }
 
-    /// Get the timestamp of the last price update
-    pub fn get_timestamp(&self, feed: &Pubkey) -> Option<i64> {
-        self.price_feeds.get(feed).map(|a| a.timestamp as i64)
+    /// Get the latest answer as an exact decimal
+    ///
+    /// Built from the scaled integer answer and the feed's decimals, with no
+    /// floating-point step on the read side.
+    #[cfg(feature = "decimal")]
+    pub fn get_price_decimal(&self, feed: &Pubkey) -> Option<crate::Decimal> {
+        let account = self.state.price_feeds.get(feed)?;
+        crate::price::decimal_from_mantissa(account.get_answer(), -(account.decimals as i32))
     }
 
-    /// Get the slot of the last price update
-    pub fn get_slot(&self, feed: &Pubkey) -> Option<u64> {
-        self.price_feeds.get(feed).map(|a| a.slot)
+    /// Get decimals for a feed
+    pub fn get_decimals(&self, feed: &Pubkey) -> Option<u8> {
+        self.state.price_feeds.get(feed).map(|a| a.decimals)
     }
 
-    /// Make an existing feed stale by setting its timestamp to `seconds_ago` in the past
+    /// Change a feed's decimals, as after a bad deploy
     ///
-    /// This is useful for testing staleness checks without changing the price.
-    pub fn make_stale(&mut self, feed: &Pubkey, seconds_ago: i64) -> Result<(), ShadowOracleError> {
-        let clock = self.svm.get_sysvar::<Clock>();
-        let stale_timestamp = (clock.unix_timestamp - seconds_ago) as u32;
-
+    /// Rewrites the header decimals and rescales every stored answer so the
+    /// USD price is unchanged for consumers that re-read decimals, while one
+    /// that cached the old decimals mis-scales it. Returns `InvalidPriceData`
+    /// for decimals above [`crate::MAX_DECIMALS`].
+    pub fn set_decimals(&mut self, feed: &Pubkey, decimals: u8) -> Result<(), ShadowOracleError> {
+        if decimals as u32 > crate::MAX_DECIMALS {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "decimals {decimals} exceeds the maximum of {}",
+                crate::MAX_DECIMALS
+            )));
+        }
         let account = self
+            .state
             .price_feeds
             .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        account.timestamp = stale_timestamp;
-
+        account.decimals = decimals;
         let account_clone = account.clone();
-        self.set_account(feed, &account_clone);
-        Ok(())
-    }
-
-    /// Create standard price feeds for common assets
-    pub fn create_standard_feeds(&mut self) -> StandardFeeds {
-        StandardFeeds {
-            sol: self.create_price_feed(PriceConf::new_usd(100.0, 0.1)),
-            btc: self.create_price_feed(PriceConf::new_usd(43000.0, 10.0)),
-            eth: self.create_price_feed(PriceConf::new_usd(2200.0, 1.0)),
-            usdc: self.create_price_feed(PriceConf::stablecoin()),
-            usdt: self.create_price_feed(PriceConf::stablecoin()),
-        }
+        self.set_account(feed, &account_clone)
     }
 
-    /// Simulate a price crash
-    pub fn simulate_crash(
+    /// Set the header `flagging_threshold` of a feed (1000 by default)
+    ///
+    /// The threshold is in units of 0.001%, so the default flags moves beyond 1%.
+    pub fn set_flagging_threshold(
         &mut self,
         feed: &Pubkey,
-        crash_percent: f64,
+        threshold: u32,
     ) -> Result<(), ShadowOracleError> {
-        let current_price = self
-            .get_price(feed)
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        let new_price = current_price * (1.0 - crash_percent / 100.0);
-        self.set_price(feed, new_price)
+        account.flagging_threshold = threshold;
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
     }
 
-    /// Simulate a depeg for stablecoins
-    pub fn simulate_depeg(
-        &mut self,
-        feed: &Pubkey,
-        new_price: f64,
-    ) -> Result<(), ShadowOracleError> {
-        self.set_price(feed, new_price)
+    /// Get the header `flagging_threshold` of a feed
+    pub fn get_flagging_threshold(&self, feed: &Pubkey) -> Option<u32> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|a| a.flagging_threshold)
     }
 
-    fn set_account(&mut self, pubkey: &Pubkey, account: &ChainlinkFeed) {
-        let data = account.to_bytes();
-
-        self.svm
-            .set_account(
-                *pubkey,
-                Account {
-                    lamports: 1_000_000_000,
-                    data,
-                    owner: self.program_id,
-                    executable: false,
-                    rent_epoch: 0,
-                },
-            )
-            .expect("Failed to set account");
+    /// Mark a feed as flagged (header `state` 2), as after a deviation beyond the threshold
+    pub fn flag(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        self.set_flagged(feed, true)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Clear a feed's flagged state (header `state` back to 1)
+    pub fn unflag(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        self.set_flagged(feed, false)
+    }
 
-    #[test]
-    fn test_create_price_feed() {
-        let mut svm = LiteSVM::new().with_sysvars();
-        let mut cl = Chainlink::new(&mut svm);
+    /// Check whether a feed is flagged
+    pub fn is_flagged(&self, feed: &Pubkey) -> Option<bool> {
+        self.state.price_feeds.get(feed).map(|a| a.flagged)
+    }
 
-        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// Get the latest round ID
+    pub fn get_latest_round(&self, feed: &Pubkey) -> Option<u32> {
+        self.state.price_feeds.get(feed).map(|a| a.round_id)
+    }
 
-        let price = cl.get_price(&feed).unwrap();
-        assert!((price - 100.0).abs() < 0.001);
+    /// Get the ring buffer header of the account stored in the SVM, for
+    /// debugging transmission readers
+    ///
+    /// Returns `(live_cursor, live_length, latest_round_id)` as serialized.
+    /// The cursor is the slot of the latest round, `(round_id - 1) %
+    /// live_length`. `None` if the feed is not tracked or its account is
+    /// missing.
+    pub fn ring_state(&self, feed: &Pubkey) -> Option<(u32, u32, u32)> {
+        self.state.price_feeds.get(feed)?;
+        let account = self.svm.get_account(feed)?;
+        ChainlinkFeed::read_ring_state(&account.data)
     }
 
-    #[test]
-    fn test_update_price() {
-        let mut svm = LiteSVM::new().with_sysvars();
-        let mut cl = Chainlink::new(&mut svm);
+    /// Set the observation and observer counts of the latest transmission
+    ///
+    /// Consumers weight trust in a round by participation; a low count lets
+    /// tests exercise low-participation rejection.
+    pub fn set_observation_counts(
+        &mut self,
+        feed: &Pubkey,
+        observations: u8,
+        observers: u8,
+    ) -> Result<(), ShadowOracleError> {
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        cl.set_price(&feed, 150.0).unwrap();
+        account.observations_count = observations;
+        account.observer_count = observers;
 
-        let price = cl.get_price(&feed).unwrap();
-        assert!((price - 150.0).abs() < 0.001);
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
     }
 
-    #[test]
-    fn test_round_increment() {
-        let mut svm = LiteSVM::new().with_sysvars();
-        let mut cl = Chainlink::new(&mut svm);
-
-        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        assert_eq!(cl.get_latest_round(&feed), Some(1));
-
-        cl.set_price(&feed, 110.0).unwrap();
-        assert_eq!(cl.get_latest_round(&feed), Some(2));
+    /// Get the `(observations_count, observer_count)` of the latest transmission
+    pub fn get_observation_counts(&self, feed: &Pubkey) -> Option<(u8, u8)> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|a| (a.observations_count, a.observer_count))
+    }
 
-        cl.set_price(&feed, 120.0).unwrap();
-        assert_eq!(cl.get_latest_round(&feed), Some(3));
+    /// Get price bounds in USD (returns `(price, price)`, Chainlink has no confidence)
+    pub fn price_bounds_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.get_price(feed).map(|p| (p, p))
     }
 
-    #[test]
-    fn test_standard_feeds() {
-        let mut svm = LiteSVM::new().with_sysvars();
-        let mut cl = Chainlink::new(&mut svm);
+    /// Compute a time-weighted average price over the last `n` retained rounds
+    ///
+    /// Each round is weighted by how long it was the latest answer: the gap to
+    /// the next round's timestamp, or to the current SVM clock for the latest
+    /// round. Falls back to a plain average if all gaps are zero. Returns `None`
+    /// if `n` is zero or fewer than `n` rounds are retained (at most 16).
+    pub fn twap(&self, feed: &Pubkey, n: u32) -> Option<f64> {
+        let rounds = self.state.price_feeds.get(feed)?.transmissions();
+        let n = n as usize;
+        if n == 0 || n > rounds.len() {
+            return None;
+        }
 
-        let feeds = cl.create_standard_feeds();
+        let now = self.svm.get_sysvar::<Clock>().unix_timestamp;
+        let window = &rounds[rounds.len() - n..];
+
+        let mut weighted_sum = 0.0;
+        let mut total_weight = 0.0;
+        for (i, round) in window.iter().enumerate() {
+            let end = window
+                .get(i + 1)
+                .map(|next| next.timestamp as i64)
+                .unwrap_or(now);
+            let weight = (end - round.timestamp as i64).max(0) as f64;
+            weighted_sum += round.price * weight;
+            total_weight += weight;
+        }
 
-        let sol_price = cl.get_price(&feeds.sol).unwrap();
-        assert!((sol_price - 100.0).abs() < 0.001);
+        if total_weight == 0.0 {
+            return Some(window.iter().map(|r| r.price).sum::<f64>() / n as f64);
+        }
+        Some(weighted_sum / total_weight)
+    }
 
-        let usdc_price = cl.get_price(&feeds.usdc).unwrap();
-        assert!((usdc_price - 1.0).abs() < 0.001);
+    /// Get a provider-agnostic reading of a feed
+    ///
+    /// Chainlink has no confidence or trading status, so the reading reports a
+    /// zero confidence and `Trading`.
+    pub fn reading(&self, feed: &Pubkey) -> Option<PriceReading> {
+        let account = self.state.price_feeds.get(feed)?;
+        Some(PriceReading {
+            price_usd: account.price,
+            conf_usd: 0.0,
+            expo: -(account.decimals as i32),
+            slot: account.slot,
+            timestamp: account.timestamp as i64,
+            status: PriceStatus::Trading,
+        })
     }
 
-    #[test]
-    fn test_simulate_crash() {
+    /// Reconstruct a `PriceConf` from a feed's current state
+    ///
+    /// The inverse of [`create_price_feed`](Self::create_price_feed): price,
+    /// decimals and publish time come from the latest round, with `expo` set
+    /// to `-decimals`. Chainlink has no confidence, EMA or trading status, so
+    /// those fields are left at their defaults.
+    pub fn reading_as_conf(&self, feed: &Pubkey) -> Option<PriceConf> {
+        self.state.reading_as_conf(feed)
+    }
+
+    /// Get the timestamp of the last price update
+    pub fn get_timestamp(&self, feed: &Pubkey) -> Option<i64> {
+        self.state.price_feeds.get(feed).map(|a| a.timestamp as i64)
+    }
+
+    /// Get the slot of the last price update
+    pub fn get_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|a| a.slot)
+    }
+
+    /// Get the program that owns a feed's account
+    pub fn get_owner(&self, feed: &Pubkey) -> Option<Pubkey> {
+        self.state.price_feeds.get(feed).map(|a| a.owner)
+    }
+
+    /// Get the data length of a feed's account as stored in the SVM
+    ///
+    /// Reads the live account, so it reflects size overrides and any later
+    /// overwrite of the account data.
+    pub fn stored_len(&self, feed: &Pubkey) -> Option<usize> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        self.svm.get_account(feed).map(|account| account.data.len())
+    }
+
+    /// SHA-256 of a feed's account data in the SVM, for golden-file tests
+    ///
+    /// `None` if the feed is not tracked or its account is missing. Slots and
+    /// timestamps make the hash differ between runs; see
+    /// [`account_hash_stable`](Self::account_hash_stable).
+    pub fn account_hash(&self, feed: &Pubkey) -> Option<[u8; 32]> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        let account = self.svm.get_account(feed)?;
+        Some(super::account_hash(&account.data, &[]))
+    }
+
+    /// SHA-256 of a feed's account data with its volatile fields zeroed
+    ///
+    /// Like [`account_hash`](Self::account_hash), but with the slot and
+    /// timestamp of every transmission zeroed first, so the hash is the same
+    /// across runs and only changes with the price data or the account layout.
+    pub fn account_hash_stable(&self, feed: &Pubkey) -> Option<[u8; 32]> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        let account = self.svm.get_account(feed)?;
+        Some(super::account_hash(&account.data, &volatile_fields()))
+    }
+
+    /// Get the slot at which a feed was created
+    pub fn created_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.created_slot)
+    }
+
+    /// Get the slot of the last `set_price` call, or the creation slot if the
+    /// feed was never updated
+    pub fn last_update_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.last_update_slot)
+    }
+
+    /// Get the prices recorded for a feed, oldest first
+    ///
+    /// Includes the creation price. Empty if history was not enabled with
+    /// [`with_history`](Self::with_history); `None` for unknown feeds.
+    pub fn history(&self, feed: &Pubkey) -> Option<&[PricePoint]> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|f| f.price_log.as_slice())
+    }
+
+    /// Get the config a feed was created from
+    ///
+    /// This is the `PriceConf` passed at creation with the provider template
+    /// applied; later updates do not change it.
+    pub fn config_for(&self, feed: &Pubkey) -> Option<&PriceConf> {
+        self.state.price_feeds.get(feed).map(|f| &f.config)
+    }
+
+    /// Get the number of price updates applied to a feed since creation
+    pub fn update_count(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|a| a.update_count)
+    }
+
+    /// Get the label of a feed, defaulting to its address for unlabeled feeds
+    pub fn label(&self, feed: &Pubkey) -> Option<String> {
+        self.state.price_feeds.get(feed)?;
+        Some(
+            self.state
+                .label(feed)
+                .map_or_else(|| feed.to_string(), str::to_string),
+        )
+    }
+
+    /// Describe a feed's current state in one line, for test output
+    pub fn describe(&self, feed: &Pubkey) -> Option<String> {
+        let price = self.get_price(feed)?;
+        Some(format!(
+            "{}: ${price}, round {}, slot {}, timestamp {}",
+            super::feed_name(feed, self.state.label(feed)),
+            self.get_latest_round(feed)?,
+            self.get_slot(feed)?,
+            self.get_timestamp(feed)?,
+        ))
+    }
+
+    /// Check whether a feed holds a price consumers would accept
+    ///
+    /// A Chainlink price is valid when the latest answer is non-zero, the feed
+    /// is not flagged and the latest round is fresh: its timestamp is at most
+    /// 60 seconds behind the SVM clock.
+    pub fn is_valid(&self, feed: &Pubkey) -> Option<bool> {
+        let account = self.state.price_feeds.get(feed)?;
+        let clock = self.svm.get_sysvar::<Clock>();
+        let fresh = clock.unix_timestamp - account.timestamp as i64 <= MAX_ROUND_AGE_SECS;
+        Some(account.get_answer() != 0 && !account.flagged && fresh)
+    }
+
+    /// Make an existing feed stale by setting its timestamp to `seconds_ago` in the past
+    ///
+    /// This is useful for testing staleness checks without changing the price.
+    pub fn make_stale(&mut self, feed: &Pubkey, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+        let clock = self.svm.get_sysvar::<Clock>();
+        let stale_timestamp = (clock.unix_timestamp - seconds_ago) as u32;
+
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.timestamp = stale_timestamp;
+
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
+
+    /// Set a feed's timestamp `seconds_ahead` in the future of the SVM clock
+    ///
+    /// Models an oracle publishing future timestamps to dodge staleness
+    /// checks, for testing that a program rejects them. The mirror image of
+    /// [`make_stale`](Self::make_stale); the price is unchanged.
+    pub fn make_future(
+        &mut self,
+        feed: &Pubkey,
+        seconds_ahead: i64,
+    ) -> Result<(), ShadowOracleError> {
+        self.make_stale(feed, -seconds_ahead)
+    }
+
+    /// Make every feed tracked by this provider stale, as in a total oracle outage
+    ///
+    /// Applies [`make_stale`](Self::make_stale) to each feed.
+    pub fn make_all_stale(&mut self, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+        let feeds: Vec<Pubkey> = self.state.price_feeds.keys().copied().collect();
+        for feed in feeds {
+            self.make_stale(&feed, seconds_ago)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a total outage: zero the feed's account data and hand the
+    /// account to the system program
+    ///
+    /// Reads that check the owner, such as
+    /// [`get_price_from_svm`](Self::get_price_from_svm), fail until the feed
+    /// is brought back with [`restore_feed`](Self::restore_feed). The cached
+    /// feed is kept; any price update also rewrites the account.
+    pub fn simulate_outage(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        if !self.state.contains(feed) {
+            return Err(ShadowOracleError::PriceFeedNotFound(feed.to_string()));
+        }
+
+        let mut account = self.svm.get_account(feed).unwrap_or_default();
+        account.data.fill(0);
+        // The system program's id is all zeros
+        account.owner = Pubkey::default();
+        self.svm
+            .set_account(*feed, account)
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+
+    /// Rewrite a feed's account from the cached state, ending a
+    /// [`simulate_outage`](Self::simulate_outage)
+    pub fn restore_feed(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .clone();
+        self.set_account(feed, &state)
+    }
+
+    /// Save a feed's current price and flagged state, to return to with
+    /// [`recover_baseline`](Self::recover_baseline) after a `simulate_*` stress
+    ///
+    /// Replaces any baseline captured earlier.
+    pub fn capture_baseline(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.baseline = Some((account.price, account.flagged));
+        Ok(())
+    }
+
+    /// Publish the price saved by [`capture_baseline`](Self::capture_baseline)
+    /// as a new round at the current clock and restore the flagged state
+    ///
+    /// Returns `InvalidPriceData` if no baseline was captured. The baseline
+    /// is kept, so a feed can be stressed and recovered repeatedly.
+    pub fn recover_baseline(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let (price, flagged) = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .baseline
+            .ok_or_else(|| {
+                ShadowOracleError::InvalidPriceData(format!("no baseline captured for {feed}"))
+            })?;
+
+        self.set_price(feed, price)?;
+        self.set_flagged(feed, flagged)
+    }
+
+    /// Create standard price feeds for common assets
+    pub fn create_standard_feeds(&mut self) -> StandardFeeds {
+        StandardFeeds {
+            sol: self.create_price_feed(PriceConf::new_usd(100.0, 0.1)),
+            btc: self.create_price_feed(PriceConf::new_usd(43000.0, 10.0)),
+            eth: self.create_price_feed(PriceConf::new_usd(2200.0, 1.0)),
+            usdc: self.create_price_feed(PriceConf::stablecoin()),
+            usdt: self.create_price_feed(PriceConf::stablecoin()),
+        }
+    }
+
+    /// Create standard price feeds with a confidence proportional to each price
+    ///
+    /// Same prices as [`create_standard_feeds`](Self::create_standard_feeds),
+    /// with a 0.1% band on SOL, BTC and ETH and a 0.01% band on USDC and USDT.
+    /// Chainlink stores no confidence, so the feeds match
+    /// `create_standard_feeds`; this exists so fixtures work on every provider.
+    pub fn create_standard_feeds_realistic(&mut self) -> StandardFeeds {
+        let major = |price: f64| PriceConf::new_usd(price, price * 0.001);
+        let stable = PriceConf::stablecoin_with_conf(0.0001);
+        StandardFeeds {
+            sol: self.create_price_feed(major(100.0)),
+            btc: self.create_price_feed(major(43000.0)),
+            eth: self.create_price_feed(major(2200.0)),
+            usdc: self.create_price_feed(stable.clone()),
+            usdt: self.create_price_feed(stable),
+        }
+    }
+
+    /// Create standard price feeds at their mainnet addresses from [`crate::feeds::chainlink`]
+    ///
+    /// The `feeds` module has no Chainlink stablecoin addresses, so USDC and USDT
+    /// are created at random addresses.
+    pub fn create_standard_feeds_at_mainnet(&mut self) -> StandardFeeds {
+        use crate::feeds::chainlink as mainnet;
+
+        StandardFeeds {
+            sol: self.create_price_feed_at(mainnet::sol_usd(), PriceConf::new_usd(100.0, 0.1)),
+            btc: self.create_price_feed_at(mainnet::btc_usd(), PriceConf::new_usd(43000.0, 10.0)),
+            eth: self.create_price_feed_at(mainnet::eth_usd(), PriceConf::new_usd(2200.0, 1.0)),
+            usdc: self.create_price_feed(PriceConf::stablecoin()),
+            usdt: self.create_price_feed(PriceConf::stablecoin()),
+        }
+    }
+
+    /// Simulate a price crash
+    pub fn simulate_crash(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let current_price = self
+            .get_price(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let new_price = current_price * (1.0 - crash_percent / 100.0);
+        self.set_price(feed, new_price)
+    }
+
+    /// Simulate a severe crash in which the feed halts
+    ///
+    /// Applies [`simulate_crash`](Self::simulate_crash), then flags the feed
+    /// (header `state` 2), Chainlink's encoding of a feed consumers should not
+    /// trust. The flag stays set until [`unflag`](Self::unflag).
+    pub fn simulate_crash_and_halt(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.simulate_crash(feed, crash_percent)?;
+        self.flag(feed)
+    }
+
+    /// Drive a feed along an arbitrary path, one update per slot
+    ///
+    /// For each slot from `start_slot` to `end_slot` inclusive, warps the SVM
+    /// to that slot and sets the price to `f(slot)`. This advances the SVM
+    /// clock, leaving it at `end_slot`.
+    pub fn simulate_with(
+        &mut self,
+        feed: &Pubkey,
+        start_slot: u64,
+        end_slot: u64,
+        f: impl Fn(u64) -> f64,
+    ) -> Result<(), ShadowOracleError> {
+        let (_, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        for slot in start_slot..=end_slot {
+            self.svm.warp_to_slot(slot);
+            self.set_price_usd(feed, f(slot), conf)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate the price impact of a swap against a pool
+    ///
+    /// Moves the price by `trade_size_usd / liquidity_usd`: the new price is
+    /// `price * (1 + trade_size_usd / liquidity_usd)`. A positive trade size is
+    /// a buy and pushes the price up, a negative one is a sell and pushes it
+    /// down. Returns `InvalidPriceData` unless `trade_size_usd` is finite and
+    /// `liquidity_usd` is positive.
+    pub fn simulate_price_impact(
+        &mut self,
+        feed: &Pubkey,
+        trade_size_usd: f64,
+        liquidity_usd: f64,
+    ) -> Result<(), ShadowOracleError> {
+        if !trade_size_usd.is_finite() {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "trade size must be finite, got {trade_size_usd}"
+            )));
+        }
+        if liquidity_usd <= 0.0 || liquidity_usd.is_nan() {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "liquidity must be positive, got {liquidity_usd}"
+            )));
+        }
+        let (price, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let new_price = price * (1.0 + trade_size_usd / liquidity_usd);
+        self.set_price_usd(feed, new_price, conf)
+    }
+
+    /// Simulate a depeg for stablecoins
+    ///
+    /// Chainlink reports no confidence, so stress is conveyed by flagging
+    /// instead: the feed is flagged when the move from the previous price
+    /// exceeds its `flagging_threshold`. From a previous price of zero, any
+    /// move away from zero counts as exceeding it. Raise the threshold with
+    /// [`set_flagging_threshold`](Self::set_flagging_threshold) to depeg
+    /// without flagging, and clear the flag with [`unflag`](Self::unflag).
+    pub fn simulate_depeg(
+        &mut self,
+        feed: &Pubkey,
+        new_price: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let (previous, threshold) = self
+            .state
+            .price_feeds
+            .get(feed)
+            .map(|a| (a.price, a.flagging_threshold))
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        self.set_price(feed, new_price)?;
+
+        let exceeded = if previous == 0.0 {
+            new_price != 0.0
+        } else {
+            ((new_price - previous) / previous).abs() * THRESHOLD_MULTIPLIER > threshold as f64
+        };
+        if exceeded {
+            self.flag(feed)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a stablecoin jittering around its peg
+    ///
+    /// Random-walks the price for `steps` updates, each moving it by up to half
+    /// of `band_usd`, one update per step. The walk starts from the current
+    /// price and is clamped to `[1 - band_usd, 1 + band_usd]`: a step that
+    /// would leave the band stops at its edge, so the feed never depegs further
+    /// than the band. The same `seed` always produces the same path. Each
+    /// update is written one slot after the previous one, advancing the SVM
+    /// clock. Returns `InvalidPriceData` unless `0 <= band_usd < 1`.
+    pub fn simulate_peg_jitter(
+        &mut self,
+        feed: &Pubkey,
+        band_usd: f64,
+        steps: u32,
+        seed: u64,
+    ) -> Result<(), ShadowOracleError> {
+        if !(0.0..1.0).contains(&band_usd) {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "peg band must be in [0, 1), got {band_usd}"
+            )));
+        }
+        let (start, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let (low, high) = (1.0 - band_usd, 1.0 + band_usd);
+        let mut rng = SplitMix64::new(seed);
+        let mut price = start.clamp(low, high);
+        for step in 0..steps {
+            if step > 0 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + 1);
+            }
+            price = (price + rng.next_signed_unit() * band_usd / 2.0).clamp(low, high);
+            self.set_price_usd(feed, price, conf)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a stablecoin gradually re-pegging to $1.00
+    ///
+    /// Moves the price an equal fraction of the way back to $1.00 on each of
+    /// `steps` updates, ending exactly at $1.00. Each step is written one slot
+    /// after the previous one, advancing the SVM clock, so per-slot reads see
+    /// the ramp.
+    pub fn simulate_repeg(&mut self, feed: &Pubkey, steps: u32) -> Result<(), ShadowOracleError> {
+        let start = self
+            .get_price(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        for step in 1..=steps {
+            if step > 1 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + 1);
+            }
+            let price = start + (1.0 - start) * step as f64 / steps as f64;
+            self.set_price(feed, price)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate an intraday range, walking the price down to `low_usd`, up to
+    /// `high_usd` and back
+    ///
+    /// The walk has three legs of `steps` equal updates each: from the current
+    /// price to `low_usd`, from `low_usd` to `high_usd`, then back to the
+    /// starting price. Each update is written one slot after the previous one,
+    /// advancing the SVM clock, so per-slot reads see both boundary crossings.
+    pub fn simulate_range(
+        &mut self,
+        feed: &Pubkey,
+        low_usd: f64,
+        high_usd: f64,
+        steps: u32,
+    ) -> Result<(), ShadowOracleError> {
+        let (start, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let legs = [(start, low_usd), (low_usd, high_usd), (high_usd, start)];
+        let mut first = true;
+        for (from, to) in legs {
+            for step in 1..=steps {
+                if !first {
+                    let slot = self.svm.get_sysvar::<Clock>().slot;
+                    self.svm.warp_to_slot(slot + 1);
+                }
+                first = false;
+                let price = from + (to - from) * step as f64 / steps as f64;
+                self.set_price_usd(feed, price, conf)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn create_feed(&mut self, address: Pubkey, conf: PriceConf, owner: Pubkey) -> Pubkey {
+        match self.try_create_feed(address, conf, owner) {
+            Ok(feed) => feed,
+            Err(err @ ShadowOracleError::InvalidPriceData(_)) => {
+                panic!("Invalid price config: {err}")
+            }
+            Err(err) => panic!("Failed to create price feed: {err}"),
+        }
+    }
+
+    fn try_create_feed(
+        &mut self,
+        address: Pubkey,
+        conf: PriceConf,
+        owner: Pubkey,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let conf = conf.with_template(&self.template)?;
+        conf.validate()?;
+        let clock = self.svm.get_sysvar::<Clock>();
+        let mut feed = ChainlinkFeed::from_conf(&conf, &clock, owner);
+        if self.state.record_history {
+            feed.record_price();
+        }
+        self.set_account(&address, &feed)?;
+        self.state.price_feeds.insert(address, feed);
+        Ok(address)
+    }
+
+    fn set_account(
+        &mut self,
+        pubkey: &Pubkey,
+        account: &ChainlinkFeed,
+    ) -> Result<(), ShadowOracleError> {
+        let data = account.to_bytes();
+
+        let lamports = if account.rent_exempt {
+            self.svm.minimum_balance_for_rent_exemption(data.len())
+        } else {
+            1_000_000_000
+        };
+
+        self.svm
+            .set_account(
+                *pubkey,
+                Account {
+                    lamports,
+                    data,
+                    owner: account.owner,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+
+    fn set_flagged(&mut self, feed: &Pubkey, flagged: bool) -> Result<(), ShadowOracleError> {
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.flagged = flagged;
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_price_feed_rent_exempt() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let funded = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let exact = cl.create_price_feed_rent_exempt(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(cl.svm.get_account(&funded).unwrap().lamports, 1_000_000_000);
+        let account = cl.svm.get_account(&exact).unwrap();
+        let minimum = cl
+            .svm
+            .minimum_balance_for_rent_exemption(account.data.len());
+        assert_eq!(account.lamports, minimum);
+        assert!(minimum < 1_000_000_000);
+
+        // Updates keep the rent-exempt balance
+        cl.set_price_usd(&exact, 110.0, 0.1).unwrap();
+        assert_eq!(cl.svm.get_account(&exact).unwrap().lamports, minimum);
+    }
+
+    #[test]
+    fn test_create_price_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let price = cl.get_price(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_to_chainlink_bytes() {
+        let conf = PriceConf {
+            publish_time: Some(1_700_000_000),
+            ..PriceConf::new_usd(100.0, 0.1)
+        };
+        let data = conf.to_chainlink_bytes();
+
+        let price = ChainlinkFeed::read_latest_answer(&data).unwrap();
+        assert!((price - 100.0).abs() < 1e-9);
+
+        // Same bytes a provider writes at slot 0
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+        let feed = cl.create_price_feed(conf);
+        assert_eq!(cl.svm.get_account(&feed).unwrap().data, data);
+    }
+
+    #[test]
+    fn test_version() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let data = cl.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[0], Chainlink::version());
+    }
+
+    #[test]
+    fn test_update_price() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.set_price(&feed, 150.0).unwrap();
+
+        let price = cl.get_price(&feed).unwrap();
+        assert!((price - 150.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_round_increment() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_latest_round(&feed), Some(1));
+
+        cl.set_price(&feed, 110.0).unwrap();
+        assert_eq!(cl.get_latest_round(&feed), Some(2));
+
+        cl.set_price(&feed, 120.0).unwrap();
+        assert_eq!(cl.get_latest_round(&feed), Some(3));
+    }
+
+    #[test]
+    fn test_standard_feeds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feeds = cl.create_standard_feeds();
+
+        let sol_price = cl.get_price(&feeds.sol).unwrap();
+        assert!((sol_price - 100.0).abs() < 0.001);
+
+        let usdc_price = cl.get_price(&feeds.usdc).unwrap();
+        assert!((usdc_price - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_standard_feeds_realistic() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feeds = cl.create_standard_feeds_realistic();
+
+        let btc_price = cl.get_price(&feeds.btc).unwrap();
+        assert!((btc_price - 43000.0).abs() < 0.001);
+        let usdt_price = cl.get_price(&feeds.usdt).unwrap();
+        assert!((usdt_price - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_simulate_crash_and_halt() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.simulate_crash_and_halt(&feed, 40.0).unwrap();
+
+        assert!((cl.get_price(&feed).unwrap() - 60.0).abs() < 0.001);
+        assert_eq!(cl.is_flagged(&feed), Some(true));
+        assert_eq!(cl.is_valid(&feed), Some(false));
+        assert_eq!(cl.svm.get_account(&feed).unwrap().data[1], STATE_FLAGGED);
+        assert!(cl
+            .simulate_crash_and_halt(&Pubkey::new_unique(), 40.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_recover_baseline() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert!(cl.recover_baseline(&feed).is_err());
+
+        cl.capture_baseline(&feed).unwrap();
+        cl.simulate_crash_and_halt(&feed, 40.0).unwrap();
+        cl.recover_baseline(&feed).unwrap();
+
+        assert!((cl.get_price(&feed).unwrap() - 100.0).abs() < 0.001);
+        assert_eq!(cl.is_flagged(&feed), Some(false));
+        assert_eq!(cl.svm.get_account(&feed).unwrap().data[1], STATE_NORMAL);
+    }
+
+    #[test]
+    fn test_simulate_crash() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.simulate_crash(&feed, 50.0).unwrap();
+
+        let price = cl.get_price(&feed).unwrap();
+        assert!((price - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_decimals() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let conf = PriceConf::new_usd(100.0, 0.1).with_decimals(6);
+        let feed = cl.create_price_feed(conf);
+
+        assert_eq!(cl.get_decimals(&feed), Some(6));
+
+        let answer = cl.get_latest_answer(&feed).unwrap();
+        assert_eq!(answer, 100_000_000); // 100 * 10^6
+    }
+
+    #[test]
+    fn test_timestamp_uses_svm_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let initial_clock = svm.get_sysvar::<Clock>();
+        let initial_timestamp = initial_clock.unix_timestamp;
+
+        let mut cl = Chainlink::new(&mut svm);
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let feed_timestamp = cl.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, initial_timestamp);
+    }
+
+    #[test]
+    fn test_slot_uses_svm_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let initial_clock = svm.get_sysvar::<Clock>();
+        let initial_slot = initial_clock.slot;
+
+        let mut cl = Chainlink::new(&mut svm);
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let feed_slot = cl.get_slot(&feed).unwrap();
+        assert_eq!(feed_slot, initial_slot);
+    }
+
+    #[test]
+    fn test_make_stale() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        // Set a realistic clock time so we can subtract from it
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000; // Nov 2023
+        svm.set_sysvar(&clock);
+
+        let mut cl = Chainlink::new(&mut svm);
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        // Make the feed 5 minutes stale
+        cl.make_stale(&feed, 300).unwrap();
+
+        let feed_timestamp = cl.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, 1_700_000_000 - 300);
+    }
+
+    #[test]
+    fn test_create_stale_feed_with_stale_by() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        // Set a realistic clock time so we can subtract from it
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000; // Nov 2023
+        svm.set_sysvar(&clock);
+
+        let current_time = clock.unix_timestamp;
+
+        let mut cl = Chainlink::new(&mut svm);
+
+        // Create a feed that's already 5 minutes old
+        let stale_conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, current_time);
+        let feed = cl.create_price_feed(stale_conf);
+
+        let feed_timestamp = cl.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, current_time - 300);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.is_valid(&feed), Some(true));
+
+        cl.set_price(&feed, 0.0).unwrap();
+        assert_eq!(cl.is_valid(&feed), Some(false));
+
+        // A non-zero answer from a round older than 60 seconds is stale
+        cl.set_price(&feed, 100.0).unwrap();
+        cl.make_stale(&feed, 61).unwrap();
+        assert_eq!(cl.is_valid(&feed), Some(false));
+        cl.make_stale(&feed, 60).unwrap();
+        assert_eq!(cl.is_valid(&feed), Some(true));
+
+        assert_eq!(cl.is_valid(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_create_price_feed_at_pda() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"oracle", b"SOL"];
+        let (address, bump) =
+            cl.create_price_feed_at_pda(seeds, &program_id, PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(
+            (address, bump),
+            Pubkey::find_program_address(seeds, &program_id)
+        );
+        assert!(cl.get_price(&address).is_some());
+        assert!(svm.get_account(&address).is_some());
+    }
+
+    #[test]
+    fn test_update_count() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.update_count(&feed), Some(0));
+
+        cl.set_price(&feed, 110.0).unwrap();
+        cl.simulate_crash(&feed, 50.0).unwrap();
+        assert_eq!(cl.update_count(&feed), Some(2));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_get_price_decimal() {
+        use std::str::FromStr;
+
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(2));
+        cl.set_price(&feed, 100.25).unwrap();
+
+        let price = cl.get_price_decimal(&feed).unwrap();
+        assert_eq!(price, crate::Decimal::from_str("100.25").unwrap());
+    }
+
+    #[test]
+    fn test_set_observation_counts() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_observation_counts(&feed), Some((3, 3)));
+
+        cl.set_observation_counts(&feed, 1, 2).unwrap();
+        assert_eq!(cl.get_observation_counts(&feed), Some((1, 2)));
+
+        // Round 1 lives in the first transmission slot
+        let data = svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[192 + 32], 1);
+        assert_eq!(data[192 + 33], 2);
+    }
+
+    #[test]
+    fn test_with_template() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let template = PriceConf::default().with_decimals(6);
+        let mut cl = Chainlink::new(&mut svm).with_template(template);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_decimals(&feed), Some(6));
+
+        let explicit = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(9));
+        assert_eq!(cl.get_decimals(&explicit), Some(9));
+    }
+
+    #[test]
+    fn test_price_bounds_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.2));
+        let (lower, upper) = cl.price_bounds_usd(&feed).unwrap();
+        assert!((lower - 100.0).abs() < 0.001);
+        assert!((upper - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_twap() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+
+        let mut cl = Chainlink::new(&mut svm);
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        for (seconds, price) in [(10, Some(200.0)), (30, Some(300.0)), (60, None)] {
+            clock.unix_timestamp += seconds;
+            cl.svm.set_sysvar(&clock);
+            if let Some(price) = price {
+                cl.set_price(&feed, price).unwrap();
+            }
+        }
+
+        // 100 for 10s, 200 for 30s, 300 for 60s
+        let twap = cl.twap(&feed, 3).unwrap();
+        assert!((twap - 250.0).abs() < 0.001);
+
+        // 200 for 30s, 300 for 60s
+        let twap = cl.twap(&feed, 2).unwrap();
+        assert!((twap - 24_000.0 / 90.0).abs() < 0.001);
+
+        assert_eq!(cl.twap(&feed, 4), None);
+        assert_eq!(cl.twap(&feed, 0), None);
+    }
+
+    #[test]
+    fn test_ring_buffer_retains_rounds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let conf = PriceConf::new_usd(100.0, 0.1).with_decimals(0);
+        let feed = cl.create_price_feed(conf);
+        for round in 2..=20 {
+            cl.set_price(&feed, round as f64 * 100.0).unwrap();
+        }
+
+        // Rounds 5..=20 are retained; round 17 sits at cursor 0
+        let data = svm.get_account(&feed).unwrap().data;
+        let answer = i128::from_le_bytes(data[192 + 16..192 + 32].try_into().unwrap());
+        assert_eq!(answer, 1700);
+    }
+
+    #[test]
+    fn test_ring_state() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.ring_state(&feed), Some((0, 16, 1)));
+
+        for round in 2..=16 {
+            cl.set_price(&feed, round as f64).unwrap();
+        }
+        assert_eq!(cl.ring_state(&feed), Some((15, 16, 16)));
+
+        // Round 17 wraps back to the first slot
+        cl.set_price(&feed, 17.0).unwrap();
+        assert_eq!(cl.ring_state(&feed), Some((0, 16, 17)));
+        cl.set_price(&feed, 18.0).unwrap();
+        assert_eq!(cl.ring_state(&feed), Some((1, 16, 18)));
+
+        assert_eq!(cl.ring_state(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_freeze_slot() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut cl = Chainlink::new(&mut svm);
 
         let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        cl.simulate_crash(&feed, 50.0).unwrap();
+        let slot = cl.get_slot(&feed).unwrap();
 
-        let price = cl.get_price(&feed).unwrap();
-        assert!((price - 50.0).abs() < 0.001);
+        cl.freeze_slot(true);
+        cl.svm.warp_to_slot(slot + 10);
+        cl.set_price(&feed, 101.0).unwrap();
+        assert_eq!(cl.get_slot(&feed), Some(slot));
+        assert!((cl.get_price(&feed).unwrap() - 101.0).abs() < 0.001);
+        assert_eq!(cl.get_latest_round(&feed), Some(1));
+
+        cl.freeze_slot(false);
+        cl.set_price(&feed, 101.0).unwrap();
+        assert_eq!(cl.get_slot(&feed), Some(slot + 10));
+        assert_eq!(cl.get_latest_round(&feed), Some(2));
     }
 
     #[test]
-    fn test_decimals() {
+    fn test_try_create_price_feed() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut cl = Chainlink::new(&mut svm);
 
-        let conf = PriceConf::new_usd(100.0, 0.1).with_decimals(6);
-        let feed = cl.create_price_feed(conf);
+        let feed = cl
+            .try_create_price_feed(PriceConf::new_usd(100.0, 0.1))
+            .unwrap();
+        assert!(cl.contains(&feed));
+
+        assert!(matches!(
+            cl.try_create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(40)),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+
+        // The SVM refuses feed data at a sysvar address
+        let slot_hashes = Pubkey::from_str("SysvarS1otHashes111111111111111111111111111").unwrap();
+        let result = cl.try_create_price_feed_at(slot_hashes, PriceConf::new_usd(100.0, 0.1));
+        assert!(matches!(result, Err(ShadowOracleError::SvmError(_))));
+        assert!(!cl.contains(&slot_hashes));
+        assert_eq!(cl.len(), 1);
+    }
 
-        assert_eq!(cl.get_decimals(&feed), Some(6));
+    #[test]
+    fn test_create_at_existing_address() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
 
-        let answer = cl.get_latest_answer(&feed).unwrap();
-        assert_eq!(answer, 100_000_000); // 100 * 10^6
+        let address = Pubkey::new_unique();
+        cl.create_price_feed_at(address, PriceConf::new_usd(100.0, 0.1));
+        cl.set_price_usd(&address, 101.0, 0.1).unwrap();
+
+        // Creating again replaces the feed rather than aliasing it
+        cl.create_price_feed_at(address, PriceConf::new_usd(200.0, 0.1));
+        assert_eq!(cl.len(), 1);
+        assert_eq!(cl.update_count(&address), Some(0));
+        let (price, _) = cl.get_price_usd(&address).unwrap();
+        assert!((price - 200.0).abs() < 0.001);
+
+        // The fallible variant refuses and leaves the feed untouched
+        assert!(matches!(
+            cl.try_create_price_feed_at(address, PriceConf::new_usd(300.0, 0.1)),
+            Err(ShadowOracleError::FeedAlreadyExists(_))
+        ));
+        let (price, _) = cl.get_price_usd(&address).unwrap();
+        assert!((price - 200.0).abs() < 0.001);
     }
 
     #[test]
-    fn test_timestamp_uses_svm_clock() {
+    fn test_stored_len() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
 
-        let initial_clock = svm.get_sysvar::<Clock>();
-        let initial_timestamp = initial_clock.unix_timestamp;
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(
+            cl.stored_len(&feed),
+            Some(HEADER_SIZE + TRANSMISSION_SIZE * NUM_TRANSMISSIONS)
+        );
+
+        // Reflects the account as stored, not the expected layout
+        let mut account = cl.svm.get_account(&feed).unwrap();
+        account.data.truncate(64);
+        cl.svm.set_account(feed, account).unwrap();
+        assert_eq!(cl.stored_len(&feed), Some(64));
+
+        assert_eq!(cl.stored_len(&Pubkey::new_unique()), None);
+    }
 
+    #[test]
+    fn test_create_price_feed_owned_by() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut cl = Chainlink::new(&mut svm);
-        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
 
-        let feed_timestamp = cl.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, initial_timestamp);
+        let adapter = Pubkey::new_unique();
+        let owned = cl.create_price_feed_owned_by(adapter, PriceConf::new_usd(100.0, 0.1));
+        let default = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_owner(&owned), Some(adapter));
+        assert_eq!(cl.get_owner(&default), Some(cl.program_id));
+
+        // The owner survives price updates
+        cl.simulate_crash(&owned, 10.0).unwrap();
+        assert_eq!(svm.get_account(&owned).unwrap().owner, adapter);
     }
 
     #[test]
-    fn test_slot_uses_svm_clock() {
+    fn test_set_price_usd_tick_size() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
 
-        let initial_clock = svm.get_sysvar::<Clock>();
-        let initial_slot = initial_clock.slot;
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.0).with_tick_size(0.01));
+        cl.set_price_usd(&feed, 100.017, 0.0).unwrap();
+        assert!((cl.get_price_from_svm(&feed).unwrap() - 100.02).abs() < 1e-9);
+
+        // The rounded tick survives scaling to the feed's decimals
+        cl.set_price_usd(&feed, 0.287, 0.0).unwrap();
+        assert_eq!(cl.get_price_from_svm(&feed), Some(0.29));
+    }
+
+    #[test]
+    fn test_set_price_usd_checked() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_quoted(1.0, 0.0, -4));
+        let result = cl.set_price_usd_checked(&feed, 1.23456, 0.0);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(cl.get_latest_round(&feed), Some(1));
+
+        cl.set_price_usd_checked(&feed, 1.2345, 0.0).unwrap();
+        assert_eq!(cl.get_price_from_svm(&feed), Some(1.2345));
+
+        // An accepted value is stored exactly, not truncated to 0.28999999
+        let usd = cl.create_price_feed(PriceConf::new_usd(1.0, 0.0));
+        cl.set_price_usd_checked(&usd, 0.29, 0.0).unwrap();
+        assert_eq!(cl.get_price_from_svm(&usd), Some(0.29));
+        assert!(cl
+            .set_price_usd_checked(&Pubkey::new_unique(), 1.0, 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_price_usd_checked_tick_size() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.0).with_tick_size(0.05));
+        assert!(matches!(
+            cl.set_price_usd_checked(&feed, 100.02, 0.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(cl.get_latest_round(&feed), Some(1));
 
+        cl.set_price_usd_checked(&feed, 100.05, 0.0).unwrap();
+        assert_eq!(cl.get_price_from_svm(&feed), Some(100.05));
+    }
+
+    #[test]
+    fn test_set_price_usd_rejects_negative_confidence() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut cl = Chainlink::new(&mut svm);
+
         let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let result = cl.set_price_usd(&feed, 100.0, -0.1);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(cl.get_latest_round(&feed), Some(1));
+    }
 
-        let feed_slot = cl.get_slot(&feed).unwrap();
-        assert_eq!(feed_slot, initial_slot);
+    #[test]
+    fn test_simulate_peg_jitter() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let a = cl.create_price_feed(PriceConf::stablecoin());
+        let b = cl.create_price_feed(PriceConf::stablecoin());
+        let c = cl.create_price_feed(PriceConf::stablecoin());
+        let slot = cl.svm.get_sysvar::<Clock>().slot;
+        cl.simulate_peg_jitter(&a, 0.002, 50, 7).unwrap();
+        assert_eq!(cl.svm.get_sysvar::<Clock>().slot, slot + 49);
+        cl.simulate_peg_jitter(&b, 0.002, 50, 7).unwrap();
+        cl.simulate_peg_jitter(&c, 0.002, 50, 8).unwrap();
+
+        let (price_a, price_b, price_c) = (
+            cl.get_price(&a).unwrap(),
+            cl.get_price(&b).unwrap(),
+            cl.get_price(&c).unwrap(),
+        );
+        assert_eq!(price_a, price_b);
+        assert_ne!(price_a, price_c);
+        for price in [price_a, price_c] {
+            assert!((0.998..=1.002).contains(&price));
+        }
+
+        assert!(cl
+            .simulate_peg_jitter(&Pubkey::new_unique(), 0.002, 5, 7)
+            .is_err());
+        let feed = cl.create_price_feed(PriceConf::stablecoin());
+        assert!(matches!(
+            cl.simulate_peg_jitter(&feed, 1.5, 5, 7),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
     }
 
     #[test]
-    fn test_make_stale() {
+    fn test_simulate_repeg() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
 
-        // Set a realistic clock time so we can subtract from it
+        let feed = cl.create_price_feed(PriceConf::stablecoin());
+        cl.simulate_depeg(&feed, 0.9).unwrap();
+        let slot = cl.get_slot(&feed).unwrap();
+
+        cl.simulate_repeg(&feed, 4).unwrap();
+
+        let price = cl.get_price(&feed).unwrap();
+        assert!((price - 1.0).abs() < 0.001);
+        assert_eq!(cl.get_slot(&feed).unwrap(), slot + 3);
+
+        // Each step is its own round, one slot apart
+        let rounds = cl.state.price_feeds[&feed].transmissions();
+        let ramp: Vec<(u64, f64)> = rounds[rounds.len() - 4..]
+            .iter()
+            .map(|t| (t.slot, t.price))
+            .collect();
+        for (i, (round_slot, price)) in ramp.iter().enumerate() {
+            assert_eq!(*round_slot, slot + i as u64);
+            assert!((price - (0.925 + 0.025 * i as f64)).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_labeled_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let labeled = cl.create_price_feed_labeled("SOL/USD", PriceConf::new_usd(100.0, 0.1));
+        let plain = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(cl.label(&labeled).as_deref(), Some("SOL/USD"));
+        assert_eq!(cl.label(&plain), Some(plain.to_string()));
+        assert_eq!(cl.label(&Pubkey::new_unique()), None);
+
+        let description = cl.describe(&labeled).unwrap();
+        assert!(description.starts_with(&format!("SOL/USD ({labeled}): $100")));
+        assert!(cl.describe(&plain).unwrap().starts_with(&plain.to_string()));
+    }
+
+    #[test]
+    fn test_reading() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.5));
+
+        let reading = cl.reading(&feed).unwrap();
+        assert!((reading.price_usd - 100.0).abs() < 0.001);
+        assert_eq!(reading.conf_usd, 0.0);
+        assert_eq!(reading.expo, -8);
+        assert_eq!(reading.slot, cl.get_slot(&feed).unwrap());
+        assert_eq!(reading.timestamp, cl.get_timestamp(&feed).unwrap());
+        assert_eq!(reading.status, PriceStatus::Trading);
+        assert!(cl.reading(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_reading_as_conf() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut chainlink = Chainlink::new(&mut svm);
+
+        let feed = chainlink.create_price_feed(PriceConf::new_quoted(100.0, 0.0, -6));
+        chainlink.set_price(&feed, 90.1).unwrap();
+
+        let conf = chainlink.reading_as_conf(&feed).unwrap();
+        assert_eq!(conf.price, 90_100_000);
+        assert_eq!(conf.conf, 0);
+        assert_eq!(conf.expo, -6);
+        assert_eq!(conf.decimals, 6);
+        assert_eq!(conf.publish_time, chainlink.get_timestamp(&feed));
+
+        let copy = chainlink.create_price_feed(conf);
+        assert_eq!(chainlink.get_price(&copy), chainlink.get_price(&feed));
+        assert!(chainlink.reading_as_conf(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_simulate_outage() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let expected = cl.get_price_from_svm(&feed);
+        assert!(expected.is_some());
+
+        cl.simulate_outage(&feed).unwrap();
+        assert_eq!(cl.get_price_from_svm(&feed), None);
+        let account = cl.svm.get_account(&feed).unwrap();
+        assert_eq!(account.owner, Pubkey::default());
+        assert!(account.data.iter().all(|b| *b == 0));
+
+        cl.restore_feed(&feed).unwrap();
+        assert_eq!(cl.get_price_from_svm(&feed), expected);
+
+        let missing = Pubkey::new_unique();
+        assert!(matches!(
+            cl.simulate_outage(&missing),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+        assert!(cl.restore_feed(&missing).is_err());
+    }
+
+    #[test]
+    fn test_conf_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.25));
+        assert_eq!(cl.conf_usd(&feed), Some(0.0));
+        cl.set_price_usd(&feed, 101.0, 0.5).unwrap();
+        assert_eq!(cl.conf_usd(&feed), Some(0.0));
+        assert_eq!(cl.conf_usd(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_make_future() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut clock = svm.get_sysvar::<Clock>();
-        clock.unix_timestamp = 1_700_000_000; // Nov 2023
+        clock.unix_timestamp = 1_700_000_000;
         svm.set_sysvar(&clock);
 
         let mut cl = Chainlink::new(&mut svm);
         let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.make_future(&feed, 120).unwrap();
 
-        // Make the feed 5 minutes stale
-        cl.make_stale(&feed, 300).unwrap();
+        let now = cl.svm.get_sysvar::<Clock>().unix_timestamp;
+        let timestamp = cl.get_timestamp(&feed).unwrap();
+        assert!(timestamp > now);
+        assert_eq!(timestamp, 1_700_000_120);
+        assert!(cl.make_future(&Pubkey::new_unique(), 120).is_err());
+    }
 
-        let feed_timestamp = cl.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, 1_700_000_000 - 300);
+    #[test]
+    fn test_account_hash() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+
+        let mut cl = Chainlink::new(&mut svm);
+        let first = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.svm.warp_to_slot(50);
+        let mut clock = cl.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 30;
+        cl.svm.set_sysvar(&clock);
+        let second = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        assert_ne!(cl.account_hash(&first), cl.account_hash(&second));
+        let stable = cl.account_hash_stable(&first).unwrap();
+        assert_eq!(cl.account_hash_stable(&second), Some(stable));
+
+        cl.set_price_usd(&second, 101.0, 0.1).unwrap();
+        assert_ne!(cl.account_hash_stable(&second), Some(stable));
+        assert_eq!(cl.account_hash(&Pubkey::new_unique()), None);
+        assert_eq!(cl.account_hash_stable(&Pubkey::new_unique()), None);
     }
 
     #[test]
-    fn test_create_stale_feed_with_stale_by() {
+    fn test_make_all_stale() {
         let mut svm = LiteSVM::new().with_sysvars();
 
         // Set a realistic clock time so we can subtract from it
         let mut clock = svm.get_sysvar::<Clock>();
-        clock.unix_timestamp = 1_700_000_000; // Nov 2023
+        clock.unix_timestamp = 1_700_000_000;
         svm.set_sysvar(&clock);
-
         let current_time = clock.unix_timestamp;
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feeds: Vec<Pubkey> = (0..3)
+            .map(|_| cl.create_price_feed(PriceConf::new_usd(100.0, 0.1)))
+            .collect();
+        cl.make_all_stale(600).unwrap();
+
+        for feed in &feeds {
+            assert_eq!(cl.get_timestamp(feed), Some(current_time - 600));
+        }
+    }
 
+    #[test]
+    fn test_contains() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut cl = Chainlink::new(&mut svm);
 
-        // Create a feed that's already 5 minutes old
-        let stale_conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, current_time);
-        let feed = cl.create_price_feed(stale_conf);
+        let feed = cl.create_price_feed(PriceConf::new_usd(0.0, 0.0));
 
-        let feed_timestamp = cl.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, current_time - 300);
+        assert!(cl.contains(&feed));
+        assert!(!cl.contains(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_created_and_last_update_slot() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        svm.warp_to_slot(100);
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.created_slot(&feed), Some(100));
+        assert_eq!(cl.last_update_slot(&feed), Some(100));
+
+        cl.svm.warp_to_slot(250);
+        cl.set_price_usd(&feed, 110.0, 0.1).unwrap();
+        assert_eq!(cl.created_slot(&feed), Some(100));
+        assert_eq!(cl.last_update_slot(&feed), Some(250));
+    }
+
+    #[test]
+    fn test_len() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+        assert!(cl.is_empty());
+
+        cl.create_standard_feeds();
+        assert_eq!(cl.len(), 5);
+        assert!(!cl.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "decimals 40 exceeds")]
+    fn test_create_rejects_out_of_range_decimals() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        cl.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(40));
+    }
+
+    #[test]
+    fn test_get_price_from_svm() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        for price in [101.0, 102.5, 123.45] {
+            cl.set_price(&feed, price).unwrap();
+        }
+
+        let price = cl.get_price_from_svm(&feed).unwrap();
+        assert!((price - 123.45).abs() < 1e-6);
+
+        assert_eq!(cl.get_price_from_svm(&Pubkey::new_unique()), None);
+
+        // Accounts owned by another program fail the owner check
+        let foreign =
+            cl.create_price_feed_owned_by(Pubkey::new_unique(), PriceConf::new_usd(100.0, 0.1));
+        assert!(cl.get_price(&foreign).is_some());
+        assert_eq!(cl.get_price_from_svm(&foreign), None);
+    }
+
+    #[test]
+    fn test_simulate_with() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm).with_history();
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.simulate_with(&feed, 10, 14, |s| 100.0 + s as f64)
+            .unwrap();
+
+        let history = cl.history(&feed).unwrap();
+        assert_eq!(history.len(), 6);
+        for (point, slot) in history[1..].iter().zip(10..=14) {
+            assert_eq!(point.slot, slot);
+            assert!((point.price_usd - (100.0 + slot as f64)).abs() < 0.001);
+        }
+        assert_eq!(cl.get_slot(&feed), Some(14));
+        assert_eq!(cl.svm.get_sysvar::<Clock>().slot, 14);
+    }
+
+    #[test]
+    fn test_simulate_price_impact() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        // A $50k buy into $1M of liquidity moves the price up 5%
+        cl.simulate_price_impact(&feed, 50_000.0, 1_000_000.0)
+            .unwrap();
+        let (price, _) = cl.get_price_usd(&feed).unwrap();
+        assert!((price - 105.0).abs() < 0.001);
+
+        // A sell of the same size moves it down 5% from there
+        cl.simulate_price_impact(&feed, -50_000.0, 1_000_000.0)
+            .unwrap();
+        let (price, _) = cl.get_price_usd(&feed).unwrap();
+        assert!((price - 99.75).abs() < 0.001);
+
+        assert!(matches!(
+            cl.simulate_price_impact(&feed, 1.0, 0.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(matches!(
+            cl.simulate_price_impact(&feed, f64::NAN, 1_000_000.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(matches!(
+            cl.simulate_price_impact(&feed, f64::INFINITY, 1_000_000.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+    }
+
+    #[test]
+    fn test_simulate_range() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm).with_history();
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.simulate_range(&feed, 90.0, 110.0, 2).unwrap();
+
+        let history = cl.history(&feed).unwrap();
+        let prices: Vec<f64> = history.iter().map(|p| p.price_usd).collect();
+        let slots: Vec<u64> = history.iter().map(|p| p.slot).collect();
+        assert_eq!(prices.len(), 7);
+        for (price, expected) in prices
+            .iter()
+            .zip([100.0, 95.0, 90.0, 100.0, 110.0, 105.0, 100.0])
+        {
+            assert!((price - expected).abs() < 0.001);
+        }
+        assert_eq!(slots, vec![0, 0, 1, 2, 3, 4, 5]);
+
+        assert!(matches!(
+            cl.simulate_range(&Pubkey::new_unique(), 90.0, 110.0, 2),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm).with_history();
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.svm.warp_to_slot(10);
+        cl.set_price_usd(&feed, 90.0, 0.1).unwrap();
+        cl.svm.warp_to_slot(20);
+        cl.set_price_usd(&feed, 95.0, 0.1).unwrap();
+
+        let history = cl.history(&feed).unwrap();
+        let prices: Vec<f64> = history.iter().map(|p| p.price_usd).collect();
+        let slots: Vec<u64> = history.iter().map(|p| p.slot).collect();
+        assert_eq!(prices.len(), 3);
+        for (price, expected) in prices.iter().zip([100.0, 90.0, 95.0]) {
+            assert!((price - expected).abs() < 0.001);
+        }
+        assert_eq!(slots, vec![0, 10, 20]);
+        assert_eq!(cl.history(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_history_off_by_default() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.set_price_usd(&feed, 90.0, 0.1).unwrap();
+
+        assert_eq!(cl.history(&feed), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_config_for() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm).with_template(PriceConf::default().with_decimals(6));
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        cl.set_price_usd(&feed, 90.0, 0.1).unwrap();
+
+        let config = cl.config_for(&feed).unwrap();
+        assert_eq!(config.price, 10_000_000_000);
+        assert_eq!(config.decimals, 6);
+        assert!(cl.config_for(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_set_decimals() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_latest_answer(&feed), Some(10_000_000_000));
+
+        cl.set_decimals(&feed, 6).unwrap();
+        assert_eq!(cl.get_decimals(&feed), Some(6));
+        assert_eq!(cl.get_latest_answer(&feed), Some(100_000_000));
+
+        let data = cl.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[130], 6);
+
+        // A consumer that cached 8 decimals now reads $1 instead of $100
+        let answer = cl.get_latest_answer(&feed).unwrap();
+        assert!((answer as f64 / 1e8 - 1.0).abs() < 1e-9);
+        let price = cl.get_price_from_svm(&feed).unwrap();
+        assert!((price - 100.0).abs() < 1e-9);
+
+        assert!(cl.set_decimals(&feed, 40).is_err());
+    }
+
+    #[test]
+    fn test_program_id_default() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        assert_eq!(
+            Chainlink::program_id_default().to_string(),
+            CHAINLINK_PROGRAM_ID
+        );
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_owner(&feed), Some(Chainlink::program_id_default()));
+    }
+
+    #[test]
+    fn test_create_standard_feeds_at_mainnet() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feeds = cl.create_standard_feeds_at_mainnet();
+        assert_eq!(feeds.sol, crate::feeds::chainlink::sol_usd());
+        assert_eq!(feeds.btc, crate::feeds::chainlink::btc_usd());
+        assert_eq!(feeds.eth, crate::feeds::chainlink::eth_usd());
+        assert_eq!(cl.len(), 5);
+
+        let (price, _) = cl.get_price_usd(&feeds.btc).unwrap();
+        assert!((price - 43000.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_flagging() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(cl.get_flagging_threshold(&feed), Some(1000));
+        assert_eq!(cl.is_flagged(&feed), Some(false));
+
+        cl.set_flagging_threshold(&feed, 250).unwrap();
+        cl.flag(&feed).unwrap();
+        let data = cl.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[1], STATE_FLAGGED);
+        assert_eq!(data[131..135], 250u32.to_le_bytes());
+        assert_eq!(cl.is_flagged(&feed), Some(true));
+
+        // Flags survive price updates until cleared
+        cl.set_price(&feed, 101.0).unwrap();
+        assert_eq!(cl.is_flagged(&feed), Some(true));
+
+        cl.unflag(&feed).unwrap();
+        let data = cl.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[1], STATE_NORMAL);
+    }
+
+    #[test]
+    fn test_simulate_depeg_flags_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut cl = Chainlink::new(&mut svm);
+
+        let feed = cl.create_price_feed(PriceConf::stablecoin());
+        cl.simulate_depeg(&feed, 0.85).unwrap();
+        assert!((cl.get_price(&feed).unwrap() - 0.85).abs() < 1e-9);
+        assert_eq!(cl.is_flagged(&feed), Some(true));
+        assert_eq!(cl.svm.get_account(&feed).unwrap().data[1], STATE_FLAGGED);
+
+        // Moves within the threshold leave the feed unflagged
+        let feed = cl.create_price_feed(PriceConf::stablecoin());
+        cl.simulate_depeg(&feed, 0.995).unwrap();
+        assert_eq!(cl.is_flagged(&feed), Some(false));
+
+        let feed = cl.create_price_feed(PriceConf::stablecoin());
+        cl.set_flagging_threshold(&feed, u32::MAX).unwrap();
+        cl.simulate_depeg(&feed, 0.85).unwrap();
+        assert_eq!(cl.is_flagged(&feed), Some(false));
+
+        // From a zero answer any move is a breach, and staying at zero is not
+        let feed = cl.create_price_feed(PriceConf::stablecoin());
+        cl.set_price(&feed, 0.0).unwrap();
+        cl.simulate_depeg(&feed, 0.0).unwrap();
+        assert_eq!(cl.is_flagged(&feed), Some(false));
+        cl.simulate_depeg(&feed, 0.5).unwrap();
+        assert_eq!(cl.is_flagged(&feed), Some(true));
     }
 }
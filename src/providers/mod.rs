@@ -3,3 +3,87 @@
 pub mod chainlink;
 pub mod pyth;
 pub mod switchboard;
+
+use sha2::{Digest, Sha256};
+use solana_pubkey::Pubkey;
+use std::ops::{Deref, DerefMut, Range};
+
+/// Oracle provider kinds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Provider {
+    Pyth,
+    Switchboard,
+    Chainlink,
+}
+
+/// Name of a feed for descriptions and error messages: `label (address)`, or
+/// just the address for unlabeled feeds
+pub(crate) fn feed_name(feed: &Pubkey, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{label} ({feed})"),
+        None => feed.to_string(),
+    }
+}
+
+/// SHA-256 of account data with the `volatile` byte ranges zeroed first
+///
+/// Ranges past the end of the data are skipped, so truncated accounts still hash.
+pub(crate) fn account_hash(data: &[u8], volatile: &[Range<usize>]) -> [u8; 32] {
+    let mut data = data.to_vec();
+    for range in volatile {
+        if let Some(bytes) = data.get_mut(range.clone()) {
+            bytes.fill(0);
+        }
+    }
+    Sha256::digest(&data).into()
+}
+
+/// Small deterministic PRNG (SplitMix64) for seeded simulations
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `[-1, 1)`
+    pub(crate) fn next_signed_unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+}
+
+/// Provider state, either owned by a standalone provider or borrowed from a
+/// [`ShadowOracle`](crate::ShadowOracle) so that it outlives the provider handle
+pub(crate) enum ProviderState<'a, T> {
+    Owned(T),
+    Borrowed(&'a mut T),
+}
+
+impl<T> Deref for ProviderState<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            ProviderState::Owned(state) => state,
+            ProviderState::Borrowed(state) => state,
+        }
+    }
+}
+
+impl<T> DerefMut for ProviderState<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            ProviderState::Owned(state) => state,
+            ProviderState::Borrowed(state) => state,
+        }
+    }
+}
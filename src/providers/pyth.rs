@@ -2,7 +2,8 @@
 //!
 //! Mock Pyth price feeds for LiteSVM testing.
 
-use crate::{PriceConf, PriceStatus, ShadowOracleError, StandardFeeds};
+use super::{ProviderState, SplitMix64};
+use crate::{PriceConf, PricePoint, PriceReading, PriceStatus, ShadowOracleError, StandardFeeds};
 use bytemuck::{Pod, Zeroable};
 use litesvm::LiteSVM;
 use solana_account::Account;
@@ -11,17 +12,54 @@ use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 use solana_signer::Signer;
 use std::collections::HashMap;
+use std::mem::offset_of;
+use std::ops::Range;
 use std::str::FromStr;
 
 /// Pyth Oracle Program ID (mainnet)
 pub const PYTH_PROGRAM_ID: &str = "FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH";
 
+/// Pyth receiver program ID (mainnet), owner of pull-oracle `PriceUpdateV2` accounts
+pub const PYTH_RECEIVER_PROGRAM_ID: &str = "rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ";
+
+/// Slots an aggregate may lag the clock before `pyth-sdk-solana` treats it as stale
+const MAX_SLOT_DIFFERENCE: u64 = 25;
+/// Seconds an aggregate may lag the clock before it counts as stale
+const MAX_PRICE_AGE_SECS: i64 = 60;
+
 /// Pyth magic number for V2 accounts
 const PYTH_MAGIC: u32 = 0xa1b2c3d4;
 /// Pyth version
 const PYTH_VERSION: u32 = 2;
+/// Product account type
+const ACCOUNT_TYPE_PRODUCT: u32 = 2;
 /// Price account type
 const ACCOUNT_TYPE_PRICE: u32 = 3;
+/// Size of a product account, header and attribute dictionary included
+const PRODUCT_ACCOUNT_SIZE: usize = 512;
+/// Product header: magic, ver, atype, size and the first price account
+const PRODUCT_HEADER_SIZE: usize = 48;
+/// Anchor discriminator of a `PriceUpdateV2` account
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+/// Size of a `PriceUpdateV2` account
+const PRICE_UPDATE_V2_SIZE: usize = 134;
+/// Offset of the price message: discriminator, write authority and a `Full`
+/// verification level
+const PRICE_MESSAGE_OFFSET: usize = 41;
+
+/// Slot and timestamp fields of a price account, which differ between runs
+pub(crate) const VOLATILE_FIELDS: [Range<usize>; 6] = [
+    field(offset_of!(PythPriceAccount, last_slot), 8),
+    field(offset_of!(PythPriceAccount, valid_slot), 8),
+    field(offset_of!(PythPriceAccount, timestamp), 8),
+    field(offset_of!(PythPriceAccount, prev_slot), 8),
+    field(offset_of!(PythPriceAccount, prev_timestamp), 8),
+    field(offset_of!(PythPriceAccount, agg.pub_slot), 8),
+];
+
+const fn field(offset: usize, len: usize) -> Range<usize> {
+    offset..offset + len
+}
 
 /// Price info structure (matches Pyth's PriceInfo)
 #[derive(Debug, Clone, Copy, Default, Pod, Zeroable)]
@@ -122,13 +160,200 @@ impl PythPriceAccount {
         self.ema_conf = (self.ema_conf * 9 + conf) / 10;
     }
 
+    /// Republish the current price: one EMA step toward spot at the clock's slot and time
+    fn tick_ema(&mut self, clock: &Clock) {
+        self.last_slot = clock.slot;
+        self.valid_slot = clock.slot;
+        self.agg.pub_slot = clock.slot;
+        self.timestamp = clock.unix_timestamp;
+
+        self.ema_price = (self.ema_price * 9 + self.agg.price) / 10;
+        self.ema_conf = (self.ema_conf * 9 + self.agg.conf) / 10;
+    }
+
     fn set_status(&mut self, status: PriceStatus) {
         self.agg.status = pyth_status(status);
     }
 
+    /// Switch to a new exponent, rescaling every stored price and confidence
+    /// so their values are unchanged (up to rounding at the new tick size)
+    fn rescale(&mut self, expo: i32) {
+        let factor = 10f64.powi(self.expo - expo);
+        let price = |value: i64| (value as f64 * factor).round() as i64;
+        let conf = |value: u64| (value as f64 * factor).round() as u64;
+
+        self.agg.price = price(self.agg.price);
+        self.agg.conf = conf(self.agg.conf);
+        self.ema_price = price(self.ema_price);
+        self.ema_conf = conf(self.ema_conf);
+        self.prev_price = price(self.prev_price);
+        self.prev_conf = conf(self.prev_conf);
+        self.expo = expo;
+    }
+
     fn as_bytes(&self) -> Vec<u8> {
         bytemuck::bytes_of(self).to_vec()
     }
+
+    /// Deserialize a price account, ignoring any padding past `SIZE`
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let account: Self = bytemuck::pod_read_unaligned(data.get(..Self::SIZE)?);
+        (account.magic == PYTH_MAGIC).then_some(account)
+    }
+}
+
+/// Price account bytes for a config at the given clock, as a new feed writes them
+pub(crate) fn price_account_bytes(conf: &PriceConf, clock: &Clock) -> Vec<u8> {
+    PythPriceAccount::from_conf(conf, clock).as_bytes()
+}
+
+/// Cached Pyth feed: the on-chain price account plus provider-side bookkeeping
+#[derive(Debug, Clone)]
+struct PythFeed {
+    account: PythPriceAccount,
+    /// Program that owns the feed account
+    owner: Pubkey,
+    /// Number of price updates applied since creation
+    update_count: u64,
+    /// Slot at which the feed was created
+    created_slot: u64,
+    /// Slot of the last `set_price` call, or the creation slot if never updated
+    last_update_slot: u64,
+    /// Config the feed was created from, after applying the provider template
+    config: PriceConf,
+    /// Optional name shown by `describe` and in error messages
+    label: Option<String>,
+    /// Prices recorded while history is enabled, oldest first
+    price_log: Vec<PricePoint>,
+    /// Account data length, if padded or truncated away from `PythPriceAccount::SIZE`
+    data_size: Option<usize>,
+    /// Whether the account holds exactly the rent-exempt minimum instead of 1 SOL
+    rent_exempt: bool,
+    /// Price, confidence and raw status saved by `capture_baseline`
+    baseline: Option<(i64, u64, u32)>,
+}
+
+impl PythFeed {
+    fn new(account: PythPriceAccount, owner: Pubkey, config: PriceConf) -> Self {
+        Self {
+            account,
+            owner,
+            update_count: 0,
+            created_slot: account.last_slot,
+            last_update_slot: account.last_slot,
+            config,
+            label: None,
+            price_log: Vec::new(),
+            data_size: None,
+            rent_exempt: false,
+            baseline: None,
+        }
+    }
+
+    /// Append the current price to the history log
+    fn record_price(&mut self) {
+        let scale = 10f64.powi(self.account.expo);
+        self.price_log.push(PricePoint {
+            slot: self.account.last_slot,
+            timestamp: self.account.timestamp,
+            price_usd: self.account.agg.price as f64 * scale,
+            conf_usd: self.account.agg.conf as f64 * scale,
+        });
+    }
+}
+
+/// Serialize a product account whose attribute dictionary holds `attributes`
+///
+/// Keys and values are stored as length-prefixed strings, as in Pyth's
+/// `ProductAccount`.
+fn product_account_bytes(
+    price_account: &Pubkey,
+    attributes: &[(&str, &str)],
+) -> Result<Vec<u8>, ShadowOracleError> {
+    let mut data = vec![0u8; PRODUCT_ACCOUNT_SIZE];
+    let mut offset = PRODUCT_HEADER_SIZE;
+    for s in attributes.iter().flat_map(|(key, value)| [key, value]) {
+        let len = u8::try_from(s.len()).map_err(|_| {
+            ShadowOracleError::InvalidPriceData(format!("product attribute too long: {s}"))
+        })?;
+        if offset + 1 + s.len() > PRODUCT_ACCOUNT_SIZE {
+            return Err(ShadowOracleError::InvalidPriceData(
+                "product attributes exceed account size".to_string(),
+            ));
+        }
+        data[offset] = len;
+        data[offset + 1..offset + 1 + s.len()].copy_from_slice(s.as_bytes());
+        offset += 1 + s.len();
+    }
+
+    data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+    data[8..12].copy_from_slice(&ACCOUNT_TYPE_PRODUCT.to_le_bytes());
+    data[12..16].copy_from_slice(&(offset as u32).to_le_bytes());
+    data[16..48].copy_from_slice(price_account.as_ref());
+    Ok(data)
+}
+
+/// Serialize a fully verified `PriceUpdateV2` mirroring `account`
+///
+/// The price message's feed id is the address of the V2 price account.
+fn price_update_v2_bytes(account: &PythPriceAccount, feed_id: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; PRICE_UPDATE_V2_SIZE];
+    data[0..8].copy_from_slice(&PRICE_UPDATE_V2_DISCRIMINATOR);
+    // write_authority (32 bytes) left zeroed
+    // verification_level: Full
+    data[40] = 1;
+
+    let message = &mut data[PRICE_MESSAGE_OFFSET..];
+    message[0..32].copy_from_slice(feed_id.as_ref());
+    message[32..40].copy_from_slice(&account.agg.price.to_le_bytes());
+    message[40..48].copy_from_slice(&account.agg.conf.to_le_bytes());
+    message[48..52].copy_from_slice(&account.expo.to_le_bytes());
+    message[52..60].copy_from_slice(&account.timestamp.to_le_bytes());
+    message[60..68].copy_from_slice(&account.prev_timestamp.to_le_bytes());
+    message[68..76].copy_from_slice(&account.ema_price.to_le_bytes());
+    message[76..84].copy_from_slice(&account.ema_conf.to_le_bytes());
+    // posted_slot
+    message[84..92].copy_from_slice(&account.last_slot.to_le_bytes());
+    data
+}
+
+/// Parse the price and confidence of a serialized `PriceUpdateV2`
+fn price_update_v2_price(data: &[u8]) -> Option<(i64, u64)> {
+    if data.len() < PRICE_UPDATE_V2_SIZE || data[0..8] != PRICE_UPDATE_V2_DISCRIMINATOR {
+        return None;
+    }
+    let message = &data[PRICE_MESSAGE_OFFSET..];
+    let price = i64::from_le_bytes(message[32..40].try_into().unwrap());
+    let conf = u64::from_le_bytes(message[40..48].try_into().unwrap());
+    Some((price, conf))
+}
+
+/// Parse the attribute dictionary of a serialized product account
+fn product_attributes(data: &[u8]) -> Option<Vec<(String, String)>> {
+    if data.len() < PRODUCT_HEADER_SIZE
+        || data[0..4] != PYTH_MAGIC.to_le_bytes()
+        || data[8..12] != ACCOUNT_TYPE_PRODUCT.to_le_bytes()
+    {
+        return None;
+    }
+    let size = u32::from_le_bytes(data[12..16].try_into().unwrap()) as usize;
+    let dict = data.get(PRODUCT_HEADER_SIZE..size)?;
+
+    let mut strings = Vec::new();
+    let mut offset = 0;
+    while offset < dict.len() {
+        let len = dict[offset] as usize;
+        let bytes = dict.get(offset + 1..offset + 1 + len)?;
+        strings.push(String::from_utf8_lossy(bytes).into_owned());
+        offset += 1 + len;
+    }
+    let mut strings = strings.into_iter();
+    let mut attributes = Vec::new();
+    while let (Some(key), Some(value)) = (strings.next(), strings.next()) {
+        attributes.push((key, value));
+    }
+    Some(attributes)
 }
 
 fn pyth_status(status: PriceStatus) -> u32 {
@@ -140,20 +365,114 @@ fn pyth_status(status: PriceStatus) -> u32 {
     }
 }
 
+fn status_from_pyth(status: u32) -> PriceStatus {
+    match status {
+        1 => PriceStatus::Trading,
+        2 => PriceStatus::Halted,
+        3 => PriceStatus::Auction,
+        _ => PriceStatus::Unknown,
+    }
+}
+
+/// Feeds of a provider, kept across handles by [`crate::ShadowOracle`]
+#[derive(Default)]
+pub(crate) struct PythState {
+    price_feeds: HashMap<Pubkey, PythFeed>,
+    /// `PriceUpdateV2` account paired with each dual feed
+    price_updates: HashMap<Pubkey, Pubkey>,
+    record_history: bool,
+}
+
+impl PythState {
+    /// Whether `feed` is tracked
+    pub(crate) fn contains(&self, feed: &Pubkey) -> bool {
+        self.price_feeds.contains_key(feed)
+    }
+
+    /// Number of tracked feeds
+    pub(crate) fn len(&self) -> usize {
+        self.price_feeds.len()
+    }
+
+    /// Addresses of all tracked feeds, in no particular order
+    pub(crate) fn feeds(&self) -> impl Iterator<Item = &Pubkey> {
+        self.price_feeds.keys()
+    }
+
+    /// Label attached to a feed at creation, if any
+    pub(crate) fn label(&self, feed: &Pubkey) -> Option<&str> {
+        self.price_feeds.get(feed)?.label.as_deref()
+    }
+
+    /// Tick size the feed's config quantizes USD updates to, if any
+    pub(crate) fn tick_size(&self, feed: &Pubkey) -> Option<f64> {
+        self.price_feeds.get(feed)?.config.tick_size
+    }
+
+    /// Replace a tracked feed's label; does nothing for untracked feeds
+    pub(crate) fn set_label(&mut self, feed: &Pubkey, label: Option<String>) {
+        if let Some(state) = self.price_feeds.get_mut(feed) {
+            state.label = label;
+        }
+    }
+
+    /// Current price and confidence of a feed in USD
+    pub(crate) fn price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.price_feeds.get(feed).map(|f| {
+            let scale = 10f64.powi(f.account.expo);
+            (
+                f.account.agg.price as f64 * scale,
+                f.account.agg.conf as f64 * scale,
+            )
+        })
+    }
+
+    /// A feed's current state as a `PriceConf`, see [`Pyth::reading_as_conf`]
+    pub(crate) fn reading_as_conf(&self, feed: &Pubkey) -> Option<PriceConf> {
+        let state = self.price_feeds.get(feed)?;
+        let account = &state.account;
+        Some(PriceConf {
+            price: account.agg.price,
+            conf: account.agg.conf,
+            expo: account.expo,
+            ema_price: Some(account.ema_price),
+            ema_conf: Some(account.ema_conf),
+            publish_time: Some(account.timestamp),
+            status: status_from_pyth(account.agg.status),
+            decimals: state.config.decimals,
+            tick_size: state.config.tick_size,
+            explicit_expo: true,
+            explicit_decimals: true,
+        })
+    }
+}
+
 /// Pyth oracle provider for LiteSVM
 pub struct Pyth<'a> {
     svm: &'a mut LiteSVM,
-    price_feeds: HashMap<Pubkey, PythPriceAccount>,
+    state: ProviderState<'a, PythState>,
     program_id: Pubkey,
+    template: PriceConf,
 }
 
 impl<'a> Pyth<'a> {
+    /// The Pyth program ID ([`PYTH_PROGRAM_ID`]) as a `Pubkey`
+    pub fn program_id_default() -> Pubkey {
+        Pubkey::from_str(PYTH_PROGRAM_ID).unwrap()
+    }
+
+    /// Magic number the mock writes at the start of every price account
+    pub fn magic() -> u32 {
+        PYTH_MAGIC
+    }
+
     /// Create a new Pyth provider
     pub fn new(svm: &'a mut LiteSVM) -> Self {
         Self {
             svm,
-            price_feeds: HashMap::new(),
-            program_id: Pubkey::from_str(PYTH_PROGRAM_ID).unwrap(),
+            state: ProviderState::Owned(PythState::default()),
+            program_id: Self::program_id_default(),
+            template: PriceConf::default(),
         }
     }
 
@@ -161,34 +480,271 @@ impl<'a> Pyth<'a> {
     pub fn with_program_id(svm: &'a mut LiteSVM, program_id: Pubkey) -> Self {
         Self {
             svm,
-            price_feeds: HashMap::new(),
+            state: ProviderState::Owned(PythState::default()),
             program_id,
+            template: PriceConf::default(),
+        }
+    }
+
+    /// Create a provider operating on state owned elsewhere
+    pub(crate) fn from_state(svm: &'a mut LiteSVM, state: &'a mut PythState) -> Self {
+        Self {
+            svm,
+            state: ProviderState::Borrowed(state),
+            program_id: Self::program_id_default(),
+            template: PriceConf::default(),
         }
     }
 
+    /// Use `template` for the exponent and decimals of new feeds
+    ///
+    /// Applies to feeds whose `PriceConf` does not set `expo`/`decimals`
+    /// explicitly, such as [`PriceConf::new_usd`]; per-feed settings from
+    /// `new_quoted`, `with_expo` or `with_decimals` still win.
+    pub fn with_template(mut self, template: PriceConf) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Record every price set on this provider's feeds, see [`history`](Self::history)
+    ///
+    /// Off by default to avoid unbounded memory growth in long scenarios.
+    pub fn with_history(mut self) -> Self {
+        self.state.record_history = true;
+        self
+    }
+
     /// Create a new price feed account
     pub fn create_price_feed(&mut self, conf: PriceConf) -> Pubkey {
         let keypair = Keypair::new();
-        let pubkey = keypair.pubkey();
+        self.create_feed(keypair.pubkey(), conf, self.program_id)
+    }
 
-        let clock = self.svm.get_sysvar::<Clock>();
-        let price_account = PythPriceAccount::from_conf(&conf, &clock);
-        self.set_account(&pubkey, &price_account);
-        self.price_feeds.insert(pubkey, price_account);
+    /// Create a new price feed account, returning an error instead of panicking
+    ///
+    /// Fails with `InvalidPriceData` for an invalid config and `SvmError` when
+    /// the SVM rejects the account.
+    pub fn try_create_price_feed(&mut self, conf: PriceConf) -> Result<Pubkey, ShadowOracleError> {
+        let keypair = Keypair::new();
+        self.try_create_feed(keypair.pubkey(), conf, self.program_id)
+    }
 
-        pubkey
+    /// Create a price feed at a specific address, returning an error instead of panicking
+    ///
+    /// See [`try_create_price_feed`](Self::try_create_price_feed). Unlike
+    /// [`create_price_feed_at`](Self::create_price_feed_at), this refuses an
+    /// address that already holds a tracked feed with `FeedAlreadyExists`.
+    pub fn try_create_price_feed_at(
+        &mut self,
+        address: Pubkey,
+        conf: PriceConf,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        if self.state.contains(&address) {
+            return Err(ShadowOracleError::FeedAlreadyExists(address.to_string()));
+        }
+        self.try_create_feed(address, conf, self.program_id)
     }
 
     /// Create a price feed at a specific address
+    ///
+    /// If the address already holds a tracked feed, that feed is replaced: its
+    /// account is rewritten from `conf` and its metadata and history start
+    /// over. Two feeds never share one address.
     pub fn create_price_feed_at(&mut self, address: Pubkey, conf: PriceConf) -> Pubkey {
-        let clock = self.svm.get_sysvar::<Clock>();
-        let price_account = PythPriceAccount::from_conf(&conf, &clock);
-        self.set_account(&address, &price_account);
-        self.price_feeds.insert(address, price_account);
-        address
+        self.create_feed(address, conf, self.program_id)
+    }
+
+    /// Create a new price feed account with a label
+    ///
+    /// The label is shown by [`describe`](Self::describe) and in
+    /// `PriceFeedNotFound` errors raised through [`crate::ShadowOracle`].
+    pub fn create_price_feed_labeled(&mut self, label: &str, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        self.state.set_label(&feed, Some(label.to_string()));
+        feed
+    }
+
+    /// Create a new labeled price feed, returning an error instead of panicking
+    ///
+    /// See [`try_create_price_feed`](Self::try_create_price_feed).
+    pub fn try_create_price_feed_labeled(
+        &mut self,
+        label: &str,
+        conf: PriceConf,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let feed = self.try_create_price_feed(conf)?;
+        self.state.set_label(&feed, Some(label.to_string()));
+        Ok(feed)
+    }
+
+    /// Create a new price feed whose account data is exactly `size` bytes long
+    ///
+    /// The serialized price account is zero-padded or truncated to `size`, and
+    /// later updates keep that length. Use it to exercise a parser's length
+    /// validation: real Pyth parsers reject anything shorter than the full
+    /// price account, so a truncated feed is expected to fail to load.
+    pub fn create_price_feed_with_size(&mut self, conf: PriceConf, size: usize) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        let state = self.state.price_feeds.get_mut(&feed).unwrap();
+        state.data_size = Some(size);
+        let state_clone = state.clone();
+        self.set_account(&feed, &state_clone)
+            .expect("Failed to set account");
+        feed
+    }
+
+    /// Create a price feed whose account carries the wrong magic number
+    ///
+    /// The cache still tracks the intended price, so getters like
+    /// [`get_price_usd`](Self::get_price_usd) report it, but readers that
+    /// validate the header — including [`get_price_from_svm`](Self::get_price_from_svm)
+    /// — reject the account. Later updates keep the bad magic.
+    pub fn create_invalid_magic_feed(&mut self, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        let state = self.state.price_feeds.get_mut(&feed).unwrap();
+        state.account.magic = !PYTH_MAGIC;
+        let state_clone = state.clone();
+        self.set_account(&feed, &state_clone)
+            .expect("Failed to set account");
+        feed
+    }
+
+    /// Create a new price feed funded with exactly the rent-exempt minimum
+    ///
+    /// The balance is `Rent::minimum_balance(data_len)` from the SVM's rent
+    /// sysvar, as a program creating the account would fund it, rather than
+    /// the 1 SOL other constructors deposit. Later updates keep the balance.
+    pub fn create_price_feed_rent_exempt(&mut self, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        let state = self.state.price_feeds.get_mut(&feed).unwrap();
+        state.rent_exempt = true;
+        let state_clone = state.clone();
+        self.set_account(&feed, &state_clone)
+            .expect("Failed to set account");
+        feed
+    }
+
+    /// Create a new price feed account owned by `owner` instead of the provider's program
+    ///
+    /// Useful when feeds in one test belong to different programs, e.g. an
+    /// oracle adapter loaded into the SVM.
+    pub fn create_price_feed_owned_by(&mut self, owner: Pubkey, conf: PriceConf) -> Pubkey {
+        let keypair = Keypair::new();
+        self.create_feed(keypair.pubkey(), conf, owner)
+    }
+
+    /// Create a price feed at the PDA derived from `seeds` and `program_id`
+    ///
+    /// Returns the derived address together with its bump seed.
+    pub fn create_price_feed_at_pda(
+        &mut self,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        conf: PriceConf,
+    ) -> (Pubkey, u8) {
+        let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+        self.create_price_feed_at(address, conf);
+        (address, bump)
+    }
+
+    /// Create a product account describing `price_feed`
+    ///
+    /// `attributes` become the product's attribute dictionary, e.g.
+    /// `[("symbol", "Crypto.SOL/USD"), ("asset_type", "Crypto")]`. The price
+    /// account's `prod` field is pointed at the new product account.
+    pub fn create_product_account(
+        &mut self,
+        price_feed: &Pubkey,
+        attributes: &[(&str, &str)],
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let data = product_account_bytes(price_feed, attributes)?;
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(price_feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(price_feed.to_string()))?;
+
+        let product = Keypair::new().pubkey();
+        state.account.prod = product.to_bytes();
+        let state_clone = state.clone();
+        self.set_account(price_feed, &state_clone)?;
+
+        self.svm
+            .set_account(
+                product,
+                Account {
+                    lamports: 1_000_000_000,
+                    data,
+                    owner: self.program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))?;
+        Ok(product)
+    }
+
+    /// Create a feed readable both as a legacy V2 price account and as a
+    /// pull-oracle `PriceUpdateV2` account
+    ///
+    /// Returns `(v2_account, price_update_v2_account)`, both populated from
+    /// `conf`. The `PriceUpdateV2` account is owned by
+    /// [`PYTH_RECEIVER_PROGRAM_ID`]. Update both with
+    /// [`set_dual_price`](Self::set_dual_price).
+    pub fn create_dual_feed(&mut self, conf: PriceConf) -> (Pubkey, Pubkey) {
+        let feed = self.create_price_feed(conf);
+        let update = Keypair::new().pubkey();
+        self.write_price_update(&feed, &update)
+            .expect("Failed to set account");
+        self.state.price_updates.insert(feed, update);
+        (feed, update)
+    }
+
+    /// Update the price of a dual feed in both its V2 and `PriceUpdateV2` accounts
+    ///
+    /// Returns `PriceFeedNotFound` unless `feed` is the V2 account of a feed
+    /// created with [`create_dual_feed`](Self::create_dual_feed).
+    pub fn set_dual_price(
+        &mut self,
+        feed: &Pubkey,
+        price: i64,
+        conf: u64,
+    ) -> Result<(), ShadowOracleError> {
+        let update = *self
+            .state
+            .price_updates
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        self.set_price(feed, price, conf)?;
+        self.write_price_update(feed, &update)
+    }
+
+    /// Read the price and confidence from a `PriceUpdateV2` account in the SVM
+    ///
+    /// Returns `None` if the account does not exist or is not a `PriceUpdateV2`.
+    pub fn get_price_update_from_svm(&self, update: &Pubkey) -> Option<(i64, u64)> {
+        let account = self.svm.get_account(update)?;
+        price_update_v2_price(&account.data)
+    }
+
+    /// Get the `symbol` attribute of a product account
+    ///
+    /// Returns `None` if the account is not a product account or has no
+    /// `symbol` attribute.
+    pub fn get_symbol(&self, product: &Pubkey) -> Option<String> {
+        let account = self.svm.get_account(product)?;
+        product_attributes(&account.data)?
+            .into_iter()
+            .find(|(key, _)| key == "symbol")
+            .map(|(_, value)| value)
     }
 
     /// Update the price of an existing feed
+    ///
+    /// `last_slot`, `valid_slot` and `agg.pub_slot` are stamped with the SVM
+    /// clock's slot, not advanced by a local counter, so after a warp the
+    /// feed's slot matches the slot the SVM reports.
     pub fn set_price(
         &mut self,
         feed: &Pubkey,
@@ -196,269 +752,2382 @@ impl<'a> Pyth<'a> {
         conf: u64,
     ) -> Result<(), ShadowOracleError> {
         let clock = self.svm.get_sysvar::<Clock>();
-        let account = self
+        let record_history = self.state.record_history;
+        let state = self
+            .state
             .price_feeds
             .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        account.set_price(price, conf, &clock);
-        let account_copy = *account;
-        self.set_account(feed, &account_copy);
-        Ok(())
+        state.account.set_price(price, conf, &clock);
+        state.update_count += 1;
+        state.last_update_slot = clock.slot;
+        if record_history {
+            state.record_price();
+        }
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
     }
 
     /// Update price using human-readable USD values
+    ///
+    /// Values are scaled by the feed's exponent and rounded to the exponent's
+    /// precision, so digits it cannot represent are silently dropped; see
+    /// [`set_price_usd_checked`](Self::set_price_usd_checked). A feed created
+    /// with [`PriceConf::with_tick_size`] first rounds the price to its tick.
+    /// Returns `InvalidPriceData` for a negative confidence.
     pub fn set_price_usd(
         &mut self,
         feed: &Pubkey,
         price: f64,
         confidence: f64,
     ) -> Result<(), ShadowOracleError> {
-        let scale = 10f64.powi(8);
-        self.set_price(feed, (price * scale) as i64, (confidence * scale) as u64)
+        crate::price::check_confidence(confidence)?;
+        let price = crate::price::round_to_tick(price, self.state.tick_size(feed));
+        let expo = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .account
+            .expo;
+        let scale = 10f64.powi(-expo);
+        self.set_price(
+            feed,
+            (price * scale).round() as i64,
+            (confidence * scale).round() as u64,
+        )
     }
 
-    /// Set the status of a price feed
-    pub fn set_status(
+    /// Update price using USD values while switching the feed to a new exponent
+    ///
+    /// The EMA and previous price are rescaled to `expo` first, so they keep
+    /// their USD values, then the update is applied as by
+    /// [`set_price_usd`](Self::set_price_usd). The account is written once.
+    /// Returns `InvalidPriceData` for an `expo` beyond [`crate::MAX_DECIMALS`]
+    /// or a negative confidence.
+    pub fn set_price_usd_expo(
         &mut self,
         feed: &Pubkey,
-        status: PriceStatus,
+        price: f64,
+        confidence: f64,
+        expo: i32,
     ) -> Result<(), ShadowOracleError> {
-        let account = self
+        crate::price::check_confidence(confidence)?;
+        if expo.unsigned_abs() > crate::MAX_DECIMALS {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "expo {expo} exceeds the maximum magnitude of {}",
+                crate::MAX_DECIMALS
+            )));
+        }
+        self.state
             .price_feeds
             .get_mut(feed)
-            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
-
-        account.set_status(status);
-        let account_copy = *account;
-        self.set_account(feed, &account_copy);
-        Ok(())
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .account
+            .rescale(expo);
+        self.set_price_usd(feed, price, confidence)
     }
 
-    /// Get the current price from a feed
-    pub fn get_price(&self, feed: &Pubkey) -> Option<(i64, u64)> {
-        self.price_feeds
+    /// Like [`set_price_usd`](Self::set_price_usd), but returns
+    /// `InvalidPriceData` instead of rounding when the price or confidence
+    /// has more precision than the feed's exponent can represent, or the
+    /// price is off the tick set by [`PriceConf::with_tick_size`]
+    pub fn set_price_usd_checked(
+        &mut self,
+        feed: &Pubkey,
+        price: f64,
+        confidence: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let expo = self
+            .state
+            .price_feeds
             .get(feed)
-            .map(|a| (a.agg.price, a.agg.conf))
-    }
-
-    /// Get the current price in human-readable USD
-    pub fn get_price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
-        self.get_price(feed).map(|(price, conf)| {
-            let scale = 10f64.powi(8);
-            (price as f64 / scale, conf as f64 / scale)
-        })
-    }
-
-    /// Get the timestamp of the last price update
-    pub fn get_timestamp(&self, feed: &Pubkey) -> Option<i64> {
-        self.price_feeds.get(feed).map(|a| a.timestamp)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .account
+            .expo;
+        crate::price::check_fits_expo(price, expo)?;
+        crate::price::check_fits_expo(confidence, expo)?;
+        crate::price::check_on_tick(price, self.state.tick_size(feed))?;
+        self.set_price_usd(feed, price, confidence)
     }
 
-    /// Get the slot of the last price update
-    pub fn get_slot(&self, feed: &Pubkey) -> Option<u64> {
-        self.price_feeds.get(feed).map(|a| a.last_slot)
+    /// Update price with a confidence interval of one standard deviation
+    ///
+    /// Sets the aggregate confidence to `sigma_usd`, so
+    /// [`conservative_price_usd`](Self::conservative_price_usd) with `n_sigma`
+    /// shifts the price by `n_sigma * sigma_usd`.
+    pub fn set_price_sigma(
+        &mut self,
+        feed: &Pubkey,
+        price_usd: f64,
+        sigma_usd: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.set_price_usd(feed, price_usd, sigma_usd)
     }
 
-    /// Make an existing feed stale by setting its timestamp to `seconds_ago` in the past
+    /// Apply one EMA smoothing step toward the current spot price
     ///
-    /// This is useful for testing staleness checks without changing the price.
-    pub fn make_stale(&mut self, feed: &Pubkey, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+    /// Spot is unchanged; the slot and timestamp advance to the SVM clock, as
+    /// when the network republishes a flat price.
+    pub fn tick_ema(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
         let clock = self.svm.get_sysvar::<Clock>();
-        let stale_timestamp = clock.unix_timestamp - seconds_ago;
-
-        let account = self
+        let state = self
+            .state
             .price_feeds
             .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        account.timestamp = stale_timestamp;
-        account.prev_timestamp = stale_timestamp - 1;
-
-        let account_copy = *account;
-        self.set_account(feed, &account_copy);
-        Ok(())
+        state.account.tick_ema(&clock);
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
     }
 
-    /// Create standard price feeds for common assets
-    pub fn create_standard_feeds(&mut self) -> StandardFeeds {
-        StandardFeeds {
-            sol: self.create_price_feed(PriceConf::new_usd(100.0, 0.1)),
-            btc: self.create_price_feed(PriceConf::new_usd(43000.0, 10.0)),
-            eth: self.create_price_feed(PriceConf::new_usd(2200.0, 1.0)),
-            usdc: self.create_price_feed(PriceConf::stablecoin()),
-            usdt: self.create_price_feed(PriceConf::stablecoin()),
-        }
+    /// Set the status of a price feed
+    pub fn set_status(
+        &mut self,
+        feed: &Pubkey,
+        status: PriceStatus,
+    ) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        state.account.set_status(status);
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
     }
 
-    /// Simulate a price crash
-    pub fn simulate_crash(
+    /// Set the `price_type` field of a price account
+    ///
+    /// Real price accounts always use `1` (price); other values let tests
+    /// exercise a parser's rejection path.
+    pub fn set_price_type(
         &mut self,
         feed: &Pubkey,
-        crash_percent: f64,
+        price_type: u32,
     ) -> Result<(), ShadowOracleError> {
-        let (current_price, current_conf) = self
-            .get_price(feed)
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        let new_price = (current_price as f64 * (1.0 - crash_percent / 100.0)) as i64;
-        let new_conf = (current_conf as f64 * 5.0) as u64;
+        state.account.price_type = price_type;
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
+    }
 
-        self.set_price(feed, new_price, new_conf)
+    /// Set the `atype` (account type) field of a price account
+    ///
+    /// Price accounts use `3`; any other value corrupts the account so that
+    /// validators checking the account type reject it.
+    pub fn set_account_type(&mut self, feed: &Pubkey, atype: u32) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        state.account.atype = atype;
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
     }
 
-    /// Simulate a depeg for stablecoins
-    pub fn simulate_depeg(
+    /// Set the `num_qt` field, the number of publishers quoting the aggregate
+    ///
+    /// New feeds report a single quoter. Lower it, down to `0`, to model thin
+    /// participation that a consumer with a quoter threshold should reject.
+    pub fn set_num_quoters(
         &mut self,
         feed: &Pubkey,
-        new_price: f64,
+        num_quoters: u32,
     ) -> Result<(), ShadowOracleError> {
-        self.set_price_usd(feed, new_price, (1.0 - new_price).abs() * 0.1 + 0.001)
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        state.account.num_qt = num_quoters;
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
     }
 
-    fn set_account(&mut self, pubkey: &Pubkey, account: &PythPriceAccount) {
-        let data = account.as_bytes();
+    /// Get the `num_qt` field of a feed
+    pub fn get_num_quoters(&self, feed: &Pubkey) -> Option<u32> {
+        self.state.price_feeds.get(feed).map(|s| s.account.num_qt)
+    }
 
-        self.svm
-            .set_account(
-                *pubkey,
-                Account {
-                    lamports: 1_000_000_000,
-                    data,
-                    owner: self.program_id,
-                    executable: false,
+    /// Get the number of feeds this provider tracks
+    pub fn len(&self) -> usize {
+        self.state.price_feeds.len()
+    }
+
+    /// Check whether this provider tracks no feeds
+    pub fn is_empty(&self) -> bool {
+        self.state.price_feeds.is_empty()
+    }
+
+    /// Check whether this provider tracks `feed`
+    pub fn contains(&self, feed: &Pubkey) -> bool {
+        self.state.price_feeds.contains_key(feed)
+    }
+
+    /// Get the current price from a feed
+    pub fn get_price(&self, feed: &Pubkey) -> Option<(i64, u64)> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|f| (f.account.agg.price, f.account.agg.conf))
+    }
+
+    /// Get the aggregate price and confidence from the account stored in the SVM
+    ///
+    /// Unlike [`get_price`](Self::get_price), this deserializes the account a
+    /// program would read rather than the provider's cache. Like a program's
+    /// owner check, it returns `None` when the account is not owned by this
+    /// provider's program ID, including feeds created with
+    /// [`create_price_feed_owned_by`](Self::create_price_feed_owned_by).
+    pub fn get_price_from_svm(&self, feed: &Pubkey) -> Option<(i64, u64)> {
+        let account = self.svm.get_account(feed)?;
+        if account.owner != self.program_id {
+            return None;
+        }
+        let price = PythPriceAccount::from_bytes(&account.data)?;
+        Some((price.agg.price, price.agg.conf))
+    }
+
+    /// Get the current price in human-readable USD
+    pub fn get_price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.state.price_usd(feed)
+    }
+
+    /// Get the confidence of a feed in USD
+    ///
+    /// The same accessor exists on every provider: Pyth reports its aggregate
+    /// confidence, Switchboard its std deviation and Chainlink zero.
+    pub fn conf_usd(&self, feed: &Pubkey) -> Option<f64> {
+        self.state.price_usd(feed).map(|(_, conf)| conf)
+    }
+
+    /// Get the confidence of a feed in basis points of its price
+    ///
+    /// Computed from the stored integers, rounded to the nearest basis point
+    /// and saturating at `u32::MAX`. `None` if the feed is not tracked or its
+    /// price is zero.
+    pub fn conf_bps(&self, feed: &Pubkey) -> Option<u32> {
+        let agg = &self.state.price_feeds.get(feed)?.account.agg;
+        let price = agg.price.unsigned_abs() as u128;
+        if price == 0 {
+            return None;
+        }
+        let bps = (agg.conf as u128 * 10_000 + price / 2) / price;
+        Some(bps.try_into().unwrap_or(u32::MAX))
+    }
+
+    /// Get the EMA price and confidence in human-readable USD
+    pub fn get_ema_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        let account = &self.state.price_feeds.get(feed)?.account;
+        let scale = 10f64.powi(account.expo);
+        Some((
+            account.ema_price as f64 * scale,
+            account.ema_conf as f64 * scale,
+        ))
+    }
+
+    /// Get the current price and confidence as exact decimals
+    ///
+    /// Built directly from the stored mantissa and exponent, with no
+    /// floating-point step in between.
+    #[cfg(feature = "decimal")]
+    pub fn get_price_decimal(&self, feed: &Pubkey) -> Option<(crate::Decimal, crate::Decimal)> {
+        let account = &self.state.price_feeds.get(feed)?.account;
+        Some((
+            crate::price::decimal_from_mantissa(account.agg.price as i128, account.expo)?,
+            crate::price::decimal_from_mantissa(account.agg.conf as i128, account.expo)?,
+        ))
+    }
+
+    /// Get a provider-agnostic reading of a feed
+    pub fn reading(&self, feed: &Pubkey) -> Option<PriceReading> {
+        let account = &self.state.price_feeds.get(feed)?.account;
+        let scale = 10f64.powi(account.expo);
+        Some(PriceReading {
+            price_usd: account.agg.price as f64 * scale,
+            conf_usd: account.agg.conf as f64 * scale,
+            expo: account.expo,
+            slot: account.last_slot,
+            timestamp: account.timestamp,
+            status: status_from_pyth(account.agg.status),
+        })
+    }
+
+    /// Reconstruct a `PriceConf` from a feed's current state
+    ///
+    /// The inverse of [`create_price_feed`](Self::create_price_feed): price,
+    /// confidence, exponent, EMA, status and publish time come from the
+    /// current account, so every field round-trips. `decimals` is not stored
+    /// on-chain and is taken from the creation config.
+    pub fn reading_as_conf(&self, feed: &Pubkey) -> Option<PriceConf> {
+        self.state.reading_as_conf(feed)
+    }
+
+    /// Get the confidence interval as `(price - conf, price + conf)` in USD
+    ///
+    /// The lower bound is the conservative collateral price, the upper bound
+    /// the conservative debt price.
+    pub fn price_bounds_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.get_price_usd(feed)
+            .map(|(price, conf)| (price - conf, price + conf))
+    }
+
+    /// Get the confidence-to-price ratio (`conf / price`)
+    ///
+    /// Computed from the stored integers, so no exponent scaling is involved.
+    /// Returns `None` for unknown feeds or a zero price.
+    pub fn conf_ratio(&self, feed: &Pubkey) -> Option<f64> {
+        let (price, conf) = self.get_price(feed)?;
+        if price == 0 {
+            return None;
+        }
+        Some(conf as f64 / price as f64)
+    }
+
+    /// Get how far the EMA price sits from spot, as a percentage of spot
+    ///
+    /// Returns `(spot - ema) / spot * 100`, computed from the stored integers,
+    /// so it is positive when spot is above the EMA. Returns `None` for
+    /// unknown feeds or a zero spot price.
+    pub fn ema_divergence_pct(&self, feed: &Pubkey) -> Option<f64> {
+        let account = &self.state.price_feeds.get(feed)?.account;
+        let spot = account.agg.price;
+        if spot == 0 {
+            return None;
+        }
+        Some((spot - account.ema_price) as f64 / spot as f64 * 100.0)
+    }
+
+    /// Get the price shifted `n_sigma` confidence intervals away from the
+    /// aggregate, as Pyth recommends for conservative valuations
+    ///
+    /// Returns `price - n_sigma * conf` when `downside` is set, otherwise
+    /// `price + n_sigma * conf`, scaled from the stored integers by the feed's
+    /// exponent.
+    pub fn conservative_price_usd(
+        &self,
+        feed: &Pubkey,
+        n_sigma: f64,
+        downside: bool,
+    ) -> Option<f64> {
+        let (price, conf) = self.get_price_usd(feed)?;
+        let shift = n_sigma * conf;
+        Some(if downside {
+            price - shift
+        } else {
+            price + shift
+        })
+    }
+
+    /// Get the timestamp of the last price update
+    pub fn get_timestamp(&self, feed: &Pubkey) -> Option<i64> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|f| f.account.timestamp)
+    }
+
+    /// Get the slot of the last price update
+    pub fn get_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|f| f.account.last_slot)
+    }
+
+    /// Get the program that owns a feed's account
+    pub fn get_owner(&self, feed: &Pubkey) -> Option<Pubkey> {
+        self.state.price_feeds.get(feed).map(|f| f.owner)
+    }
+
+    /// Get the data length of a feed's account as stored in the SVM
+    ///
+    /// Reads the live account, so it reflects size overrides and any later
+    /// overwrite of the account data.
+    pub fn stored_len(&self, feed: &Pubkey) -> Option<usize> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        self.svm.get_account(feed).map(|account| account.data.len())
+    }
+
+    /// SHA-256 of a feed's account data in the SVM, for golden-file tests
+    ///
+    /// `None` if the feed is not tracked or its account is missing. Slots and
+    /// timestamps make the hash differ between runs; see
+    /// [`account_hash_stable`](Self::account_hash_stable).
+    pub fn account_hash(&self, feed: &Pubkey) -> Option<[u8; 32]> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        let account = self.svm.get_account(feed)?;
+        Some(super::account_hash(&account.data, &[]))
+    }
+
+    /// SHA-256 of a feed's account data with its volatile fields zeroed
+    ///
+    /// Like [`account_hash`](Self::account_hash), but with the slot and
+    /// timestamp fields zeroed first, so the hash is the same across runs and
+    /// only changes with the price data or the account layout.
+    pub fn account_hash_stable(&self, feed: &Pubkey) -> Option<[u8; 32]> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        let account = self.svm.get_account(feed)?;
+        Some(super::account_hash(&account.data, &VOLATILE_FIELDS))
+    }
+
+    /// Get the slot at which a feed was created
+    pub fn created_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.created_slot)
+    }
+
+    /// Get the slot of the last `set_price` call, or the creation slot if the
+    /// feed was never updated
+    pub fn last_update_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.last_update_slot)
+    }
+
+    /// Get the prices recorded for a feed, oldest first
+    ///
+    /// Includes the creation price. Empty if history was not enabled with
+    /// [`with_history`](Self::with_history); `None` for unknown feeds.
+    pub fn history(&self, feed: &Pubkey) -> Option<&[PricePoint]> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|f| f.price_log.as_slice())
+    }
+
+    /// Get the config a feed was created from
+    ///
+    /// This is the `PriceConf` passed at creation with the provider template
+    /// applied; later updates do not change it.
+    pub fn config_for(&self, feed: &Pubkey) -> Option<&PriceConf> {
+        self.state.price_feeds.get(feed).map(|f| &f.config)
+    }
+
+    /// Get the number of price updates applied to a feed since creation
+    pub fn update_count(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.update_count)
+    }
+
+    /// Get the label of a feed, defaulting to its address for unlabeled feeds
+    pub fn label(&self, feed: &Pubkey) -> Option<String> {
+        self.state.price_feeds.get(feed)?;
+        Some(
+            self.state
+                .label(feed)
+                .map_or_else(|| feed.to_string(), str::to_string),
+        )
+    }
+
+    /// Describe a feed's current state in one line, for test output
+    pub fn describe(&self, feed: &Pubkey) -> Option<String> {
+        let (price, conf) = self.get_price_usd(feed)?;
+        Some(format!(
+            "{}: ${price} ± {conf}, slot {}, timestamp {}",
+            super::feed_name(feed, self.state.label(feed)),
+            self.get_slot(feed)?,
+            self.get_timestamp(feed)?,
+        ))
+    }
+
+    /// Check whether a feed holds a price consumers would accept
+    ///
+    /// A Pyth price is valid when the aggregate status is `Trading` and the
+    /// aggregate price is non-zero.
+    pub fn is_valid(&self, feed: &Pubkey) -> Option<bool> {
+        self.state.price_feeds.get(feed).map(|f| {
+            f.account.agg.status == pyth_status(PriceStatus::Trading) && f.account.agg.price != 0
+        })
+    }
+
+    /// Check whether a feed's aggregate can be traded on right now
+    ///
+    /// True only when the feed [`is_valid`](Self::is_valid) (status `Trading`
+    /// and a non-zero price) and is fresh: its `agg.pub_slot` is at most 25
+    /// slots behind the SVM clock, as `pyth-sdk-solana` requires, and its
+    /// timestamp at most 60 seconds old.
+    pub fn is_tradeable(&self, feed: &Pubkey) -> Option<bool> {
+        let account = &self.state.price_feeds.get(feed)?.account;
+        let clock = self.svm.get_sysvar::<Clock>();
+        let fresh = clock.slot.saturating_sub(account.agg.pub_slot) <= MAX_SLOT_DIFFERENCE
+            && clock.unix_timestamp - account.timestamp <= MAX_PRICE_AGE_SECS;
+        Some(self.is_valid(feed)? && fresh)
+    }
+
+    /// Make an existing feed stale by setting its timestamp to `seconds_ago` in the past
+    ///
+    /// This is useful for testing staleness checks without changing the price.
+    pub fn make_stale(&mut self, feed: &Pubkey, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+        let clock = self.svm.get_sysvar::<Clock>();
+        let stale_timestamp = clock.unix_timestamp - seconds_ago;
+
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        state.account.timestamp = stale_timestamp;
+        state.account.prev_timestamp = stale_timestamp - 1;
+
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
+    }
+
+    /// Set a feed's timestamp `seconds_ahead` in the future of the SVM clock
+    ///
+    /// Models an oracle publishing future timestamps to dodge staleness
+    /// checks, for testing that a program rejects them. The mirror image of
+    /// [`make_stale`](Self::make_stale); the price is unchanged.
+    pub fn make_future(
+        &mut self,
+        feed: &Pubkey,
+        seconds_ahead: i64,
+    ) -> Result<(), ShadowOracleError> {
+        self.make_stale(feed, -seconds_ahead)
+    }
+
+    /// Set a feed's publish time to the SVM clock's `unix_timestamp + delta_seconds`
+    ///
+    /// A positive delta puts the oracle ahead of block time, a negative one
+    /// behind it, for testing checks that order oracle time against block
+    /// time. Equivalent to [`make_future`](Self::make_future) with
+    /// `delta_seconds`, or [`make_stale`](Self::make_stale) with its negation.
+    pub fn set_publish_time_relative(
+        &mut self,
+        feed: &Pubkey,
+        delta_seconds: i64,
+    ) -> Result<(), ShadowOracleError> {
+        self.make_future(feed, delta_seconds)
+    }
+
+    /// Pin a feed's publish slot and timestamp to exact values
+    ///
+    /// Writes `valid_slot`, `last_slot`, `agg.pub_slot` and `timestamp` in a
+    /// single account update, leaving the price untouched.
+    pub fn set_publish(
+        &mut self,
+        feed: &Pubkey,
+        slot: u64,
+        timestamp: i64,
+    ) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        state.account.valid_slot = slot;
+        state.account.last_slot = slot;
+        state.account.agg.pub_slot = slot;
+        state.account.timestamp = timestamp;
+
+        let state_clone = state.clone();
+        self.set_account(feed, &state_clone)
+    }
+
+    /// Make every feed tracked by this provider stale, as in a total oracle outage
+    ///
+    /// Applies [`make_stale`](Self::make_stale) to each feed.
+    pub fn make_all_stale(&mut self, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+        let feeds: Vec<Pubkey> = self.state.price_feeds.keys().copied().collect();
+        for feed in feeds {
+            self.make_stale(&feed, seconds_ago)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a total outage: zero the feed's account data and hand the
+    /// account to the system program
+    ///
+    /// Reads that check the owner, such as
+    /// [`get_price_from_svm`](Self::get_price_from_svm), fail until the feed
+    /// is brought back with [`restore_feed`](Self::restore_feed). The cached
+    /// feed is kept; any price update also rewrites the account.
+    pub fn simulate_outage(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        if !self.state.contains(feed) {
+            return Err(ShadowOracleError::PriceFeedNotFound(feed.to_string()));
+        }
+
+        let mut account = self.svm.get_account(feed).unwrap_or_default();
+        account.data.fill(0);
+        // The system program's id is all zeros
+        account.owner = Pubkey::default();
+        self.svm
+            .set_account(*feed, account)
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+
+    /// Rewrite a feed's account from the cached state, ending a
+    /// [`simulate_outage`](Self::simulate_outage)
+    pub fn restore_feed(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .clone();
+        self.set_account(feed, &state)
+    }
+
+    /// Save a feed's current price, confidence and status, to return to with
+    /// [`recover_baseline`](Self::recover_baseline) after a `simulate_*` stress
+    ///
+    /// Replaces any baseline captured earlier.
+    pub fn capture_baseline(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        state.baseline = Some((
+            state.account.agg.price,
+            state.account.agg.conf,
+            state.account.agg.status,
+        ));
+        Ok(())
+    }
+
+    /// Publish the price, confidence and status saved by
+    /// [`capture_baseline`](Self::capture_baseline) as a new update at the
+    /// current clock
+    ///
+    /// Returns `InvalidPriceData` if no baseline was captured. The baseline
+    /// is kept, so a feed can be stressed and recovered repeatedly.
+    pub fn recover_baseline(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+        let (price, conf, status) = state.baseline.ok_or_else(|| {
+            ShadowOracleError::InvalidPriceData(format!("no baseline captured for {feed}"))
+        })?;
+
+        state.account.agg.status = status;
+        self.set_price(feed, price, conf)
+    }
+
+    /// Create standard price feeds for common assets
+    pub fn create_standard_feeds(&mut self) -> StandardFeeds {
+        StandardFeeds {
+            sol: self.create_price_feed(PriceConf::new_usd(100.0, 0.1)),
+            btc: self.create_price_feed(PriceConf::new_usd(43000.0, 10.0)),
+            eth: self.create_price_feed(PriceConf::new_usd(2200.0, 1.0)),
+            usdc: self.create_price_feed(PriceConf::stablecoin()),
+            usdt: self.create_price_feed(PriceConf::stablecoin()),
+        }
+    }
+
+    /// Create standard price feeds with a confidence proportional to each price
+    ///
+    /// Same prices as [`create_standard_feeds`](Self::create_standard_feeds),
+    /// with a 0.1% band on SOL, BTC and ETH and a 0.01% band on USDC and USDT.
+    pub fn create_standard_feeds_realistic(&mut self) -> StandardFeeds {
+        let major = |price: f64| PriceConf::new_usd(price, price * 0.001);
+        let stable = PriceConf::stablecoin_with_conf(0.0001);
+        StandardFeeds {
+            sol: self.create_price_feed(major(100.0)),
+            btc: self.create_price_feed(major(43000.0)),
+            eth: self.create_price_feed(major(2200.0)),
+            usdc: self.create_price_feed(stable.clone()),
+            usdt: self.create_price_feed(stable),
+        }
+    }
+
+    /// Create standard price feeds at their mainnet addresses from [`crate::feeds::pyth`]
+    pub fn create_standard_feeds_at_mainnet(&mut self) -> StandardFeeds {
+        use crate::feeds::pyth as mainnet;
+
+        StandardFeeds {
+            sol: self.create_price_feed_at(mainnet::sol_usd(), PriceConf::new_usd(100.0, 0.1)),
+            btc: self.create_price_feed_at(mainnet::btc_usd(), PriceConf::new_usd(43000.0, 10.0)),
+            eth: self.create_price_feed_at(mainnet::eth_usd(), PriceConf::new_usd(2200.0, 1.0)),
+            usdc: self.create_price_feed_at(mainnet::usdc_usd(), PriceConf::stablecoin()),
+            usdt: self.create_price_feed_at(mainnet::usdt_usd(), PriceConf::stablecoin()),
+        }
+    }
+
+    /// Simulate a price crash
+    pub fn simulate_crash(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let (current_price, current_conf) = self
+            .get_price(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let new_price = (current_price as f64 * (1.0 - crash_percent / 100.0)) as i64;
+        let new_conf = (current_conf as f64 * 5.0) as u64;
+
+        self.set_price(feed, new_price, new_conf)
+    }
+
+    /// Simulate a severe crash in which the feed halts
+    ///
+    /// Applies [`simulate_crash`](Self::simulate_crash) (price down by
+    /// `crash_percent`, confidence widened 5x), then sets the status to
+    /// `Halted`. The status stays halted until reset with
+    /// [`set_status`](Self::set_status).
+    pub fn simulate_crash_and_halt(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.simulate_crash(feed, crash_percent)?;
+        self.set_status(feed, PriceStatus::Halted)
+    }
+
+    /// Simulate a crash the way oracles report one: lagging behind the market
+    ///
+    /// Applies [`simulate_crash`](Self::simulate_crash) (price down by
+    /// `crash_percent`, confidence widened 5x), then rewinds the timestamp by
+    /// `lag_seconds` as [`make_stale`](Self::make_stale) does.
+    pub fn simulate_crash_realistic(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+        lag_seconds: i64,
+    ) -> Result<(), ShadowOracleError> {
+        self.simulate_crash(feed, crash_percent)?;
+        self.make_stale(feed, lag_seconds)
+    }
+
+    /// Drive a feed along an arbitrary path, one update per slot
+    ///
+    /// For each slot from `start_slot` to `end_slot` inclusive, warps the SVM
+    /// to that slot and sets the price to `f(slot)`, keeping the current
+    /// confidence. This advances the SVM clock, leaving it at `end_slot`.
+    pub fn simulate_with(
+        &mut self,
+        feed: &Pubkey,
+        start_slot: u64,
+        end_slot: u64,
+        f: impl Fn(u64) -> f64,
+    ) -> Result<(), ShadowOracleError> {
+        let (_, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        for slot in start_slot..=end_slot {
+            self.svm.warp_to_slot(slot);
+            self.set_price_usd(feed, f(slot), conf)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate the price impact of a swap against a pool
+    ///
+    /// Moves the price by `trade_size_usd / liquidity_usd`: the new price is
+    /// `price * (1 + trade_size_usd / liquidity_usd)`. A positive trade size is
+    /// a buy and pushes the price up, a negative one is a sell and pushes it
+    /// down. The confidence is unchanged. Returns `InvalidPriceData` unless
+    /// `trade_size_usd` is finite and `liquidity_usd` is positive.
+    pub fn simulate_price_impact(
+        &mut self,
+        feed: &Pubkey,
+        trade_size_usd: f64,
+        liquidity_usd: f64,
+    ) -> Result<(), ShadowOracleError> {
+        if !trade_size_usd.is_finite() {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "trade size must be finite, got {trade_size_usd}"
+            )));
+        }
+        if liquidity_usd <= 0.0 || liquidity_usd.is_nan() {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "liquidity must be positive, got {liquidity_usd}"
+            )));
+        }
+        let (price, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let new_price = price * (1.0 + trade_size_usd / liquidity_usd);
+        self.set_price_usd(feed, new_price, conf)
+    }
+
+    /// Simulate a depeg for stablecoins
+    ///
+    /// The confidence widens with the distance from $1.00: 10% of it plus
+    /// $0.001. Use [`simulate_depeg_with`](Self::simulate_depeg_with) to
+    /// choose the confidence.
+    pub fn simulate_depeg(
+        &mut self,
+        feed: &Pubkey,
+        new_price: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.simulate_depeg_with(feed, new_price, (1.0 - new_price).abs() * 0.1 + 0.001)
+    }
+
+    /// Simulate a depeg for stablecoins with an exact confidence in USD
+    ///
+    /// Nothing is added to `conf`, so a zero confidence depeg tests rejection
+    /// on price alone.
+    pub fn simulate_depeg_with(
+        &mut self,
+        feed: &Pubkey,
+        new_price: f64,
+        conf: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.set_price_usd(feed, new_price, conf)
+    }
+
+    /// Simulate a stablecoin jittering around its peg
+    ///
+    /// Random-walks the price for `steps` updates, each moving it by up to half
+    /// of `band_usd`, keeping the current confidence. The walk starts from the
+    /// current price and is clamped to `[1 - band_usd, 1 + band_usd]`: a step
+    /// that would leave the band stops at its edge, so the feed never depegs
+    /// further than the band. The same `seed` always produces the same path.
+    /// Each update is written one slot after the previous one, advancing the
+    /// SVM clock. Returns `InvalidPriceData` unless `0 <= band_usd < 1`.
+    pub fn simulate_peg_jitter(
+        &mut self,
+        feed: &Pubkey,
+        band_usd: f64,
+        steps: u32,
+        seed: u64,
+    ) -> Result<(), ShadowOracleError> {
+        if !(0.0..1.0).contains(&band_usd) {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "peg band must be in [0, 1), got {band_usd}"
+            )));
+        }
+        let (start, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let (low, high) = (1.0 - band_usd, 1.0 + band_usd);
+        let mut rng = SplitMix64::new(seed);
+        let mut price = start.clamp(low, high);
+        for step in 0..steps {
+            if step > 0 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + 1);
+            }
+            price = (price + rng.next_signed_unit() * band_usd / 2.0).clamp(low, high);
+            self.set_price_usd(feed, price, conf)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a stablecoin gradually re-pegging to $1.00
+    ///
+    /// Moves the price an equal fraction of the way back to $1.00 on each of
+    /// `steps` updates, ending exactly at $1.00, narrowing the confidence as it
+    /// goes. Each step is written one slot after the previous one, advancing
+    /// the SVM clock, so per-slot reads see the ramp.
+    pub fn simulate_repeg(&mut self, feed: &Pubkey, steps: u32) -> Result<(), ShadowOracleError> {
+        let (start, _) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        for step in 1..=steps {
+            if step > 1 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + 1);
+            }
+            let price = start + (1.0 - start) * step as f64 / steps as f64;
+            self.simulate_depeg(feed, price)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate an intraday range, walking the price down to `low_usd`, up to
+    /// `high_usd` and back
+    ///
+    /// The walk has three legs of `steps` equal updates each: from the current
+    /// price to `low_usd`, from `low_usd` to `high_usd`, then back to the
+    /// starting price, keeping the current confidence. Each update is written
+    /// one slot after the previous one, advancing the SVM clock, so per-slot
+    /// reads see both boundary crossings.
+    pub fn simulate_range(
+        &mut self,
+        feed: &Pubkey,
+        low_usd: f64,
+        high_usd: f64,
+        steps: u32,
+    ) -> Result<(), ShadowOracleError> {
+        let (start, conf) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let legs = [(start, low_usd), (low_usd, high_usd), (high_usd, start)];
+        let mut first = true;
+        for (from, to) in legs {
+            for step in 1..=steps {
+                if !first {
+                    let slot = self.svm.get_sysvar::<Clock>().slot;
+                    self.svm.warp_to_slot(slot + 1);
+                }
+                first = false;
+                let price = from + (to - from) * step as f64 / steps as f64;
+                self.set_price_usd(feed, price, conf)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn create_feed(&mut self, address: Pubkey, conf: PriceConf, owner: Pubkey) -> Pubkey {
+        match self.try_create_feed(address, conf, owner) {
+            Ok(feed) => feed,
+            Err(err @ ShadowOracleError::InvalidPriceData(_)) => {
+                panic!("Invalid price config: {err}")
+            }
+            Err(err) => panic!("Failed to create price feed: {err}"),
+        }
+    }
+
+    fn try_create_feed(
+        &mut self,
+        address: Pubkey,
+        conf: PriceConf,
+        owner: Pubkey,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let conf = conf.with_template(&self.template)?;
+        conf.validate()?;
+        let clock = self.svm.get_sysvar::<Clock>();
+        let mut state = PythFeed::new(
+            PythPriceAccount::from_conf(&conf, &clock),
+            owner,
+            conf.clone(),
+        );
+        if self.state.record_history {
+            state.record_price();
+        }
+        self.set_account(&address, &state)?;
+        self.state.price_feeds.insert(address, state);
+        Ok(address)
+    }
+
+    fn write_price_update(
+        &mut self,
+        feed: &Pubkey,
+        update: &Pubkey,
+    ) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+        let data = price_update_v2_bytes(&state.account, feed);
+
+        self.svm
+            .set_account(
+                *update,
+                Account {
+                    lamports: 1_000_000_000,
+                    data,
+                    owner: Pubkey::from_str(PYTH_RECEIVER_PROGRAM_ID).unwrap(),
+                    executable: false,
                     rent_epoch: 0,
                 },
             )
-            .expect("Failed to set account");
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+
+    fn set_account(&mut self, pubkey: &Pubkey, state: &PythFeed) -> Result<(), ShadowOracleError> {
+        let mut data = state.account.as_bytes();
+        if let Some(size) = state.data_size {
+            data.resize(size, 0);
+        }
+
+        let lamports = if state.rent_exempt {
+            self.svm.minimum_balance_for_rent_exemption(data.len())
+        } else {
+            1_000_000_000
+        };
+
+        self.svm
+            .set_account(
+                *pubkey,
+                Account {
+                    lamports,
+                    data,
+                    owner: state.owner,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_price_feed_rent_exempt() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let funded = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let exact = pyth.create_price_feed_rent_exempt(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(
+            pyth.svm.get_account(&funded).unwrap().lamports,
+            1_000_000_000
+        );
+        let account = pyth.svm.get_account(&exact).unwrap();
+        let minimum = pyth
+            .svm
+            .minimum_balance_for_rent_exemption(account.data.len());
+        assert_eq!(account.lamports, minimum);
+        assert!(minimum < 1_000_000_000);
+
+        // Updates keep the rent-exempt balance
+        pyth.set_price_usd(&exact, 110.0, 0.1).unwrap();
+        assert_eq!(pyth.svm.get_account(&exact).unwrap().lamports, minimum);
+    }
+
+    #[test]
+    fn test_create_price_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let (price, conf) = pyth.get_price(&feed).unwrap();
+        assert_eq!(price, 10000000000);
+        assert_eq!(conf, 10000000);
+    }
+
+    #[test]
+    fn test_to_pyth_bytes() {
+        let conf = PriceConf {
+            publish_time: Some(1_700_000_000),
+            ..PriceConf::new_usd(100.0, 0.1)
+        };
+        let data = conf.to_pyth_bytes();
+
+        let account = PythPriceAccount::from_bytes(&data).unwrap();
+        assert_eq!(account.agg.price, conf.price);
+        assert_eq!(account.agg.conf, conf.conf);
+        assert_eq!(account.expo, conf.expo);
+        assert_eq!(account.timestamp, 1_700_000_000);
+
+        // Same bytes a provider writes at slot 0
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(conf);
+        assert_eq!(pyth.svm.get_account(&feed).unwrap().data, data);
+    }
+
+    #[test]
+    fn test_magic() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[0..4], Pyth::magic().to_le_bytes());
+    }
+
+    #[test]
+    fn test_update_price() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_usd(&feed, 150.0, 0.2).unwrap();
+
+        let (price, _) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 150.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_standard_feeds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feeds = pyth.create_standard_feeds();
+
+        let (sol_price, _) = pyth.get_price_usd(&feeds.sol).unwrap();
+        assert!((sol_price - 100.0).abs() < 0.001);
+
+        let (usdc_price, _) = pyth.get_price_usd(&feeds.usdc).unwrap();
+        assert!((usdc_price - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_standard_feeds_realistic() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feeds = pyth.create_standard_feeds_realistic();
+
+        for (feed, price, ratio) in [
+            (feeds.sol, 100.0, 0.001),
+            (feeds.btc, 43000.0, 0.001),
+            (feeds.eth, 2200.0, 0.001),
+            (feeds.usdc, 1.0, 0.0001),
+            (feeds.usdt, 1.0, 0.0001),
+        ] {
+            let (actual, conf) = pyth.get_price_usd(&feed).unwrap();
+            assert!((actual - price).abs() < 0.001);
+            assert!((conf - price * ratio).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_simulate_crash() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.simulate_crash(&feed, 50.0).unwrap();
+
+        let (price, _) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_simulate_depeg() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::stablecoin());
+        pyth.simulate_depeg(&feed, 0.95).unwrap();
+
+        let (price, _) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 0.95).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_simulate_depeg_with() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::stablecoin());
+        pyth.simulate_depeg_with(&feed, 0.95, 0.0).unwrap();
+        assert_eq!(pyth.get_price(&feed), Some((95_000_000, 0)));
+
+        pyth.simulate_depeg(&feed, 0.9).unwrap();
+        let (_, conf) = pyth.get_price_usd(&feed).unwrap();
+        assert!((conf - 0.011).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_timestamp_uses_svm_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let initial_clock = svm.get_sysvar::<Clock>();
+        let initial_timestamp = initial_clock.unix_timestamp;
+
+        let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let feed_timestamp = pyth.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, initial_timestamp);
+    }
+
+    #[test]
+    fn test_slot_uses_svm_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let initial_clock = svm.get_sysvar::<Clock>();
+        let initial_slot = initial_clock.slot;
+
+        let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let feed_slot = pyth.get_slot(&feed).unwrap();
+        assert_eq!(feed_slot, initial_slot);
+    }
+
+    #[test]
+    fn test_created_feed_slots_follow_svm_slot() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        svm.warp_to_slot(500_000);
+
+        let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        let account: PythPriceAccount = bytemuck::pod_read_unaligned(&data);
+        assert_eq!(account.valid_slot, 500_000);
+        assert_eq!(account.last_slot, 500_000);
+        assert_eq!(account.prev_slot, 499_999);
+    }
+
+    #[test]
+    fn test_make_stale() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let clock = svm.get_sysvar::<Clock>();
+        let current_time = clock.unix_timestamp;
+
+        let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        // Make the feed 5 minutes stale
+        pyth.make_stale(&feed, 300).unwrap();
+
+        let feed_timestamp = pyth.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, current_time - 300);
+    }
+
+    #[test]
+    fn test_set_publish() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_publish(&feed, 4242, 1_700_000_000).unwrap();
+
+        assert_eq!(pyth.get_slot(&feed), Some(4242));
+        assert_eq!(pyth.get_timestamp(&feed), Some(1_700_000_000));
+
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        let account = PythPriceAccount::from_bytes(&data).unwrap();
+        assert_eq!(account.valid_slot, 4242);
+        assert_eq!(account.last_slot, 4242);
+        assert_eq!(account.agg.pub_slot, 4242);
+        assert_eq!(account.timestamp, 1_700_000_000);
+        assert_eq!(account.agg.price, 10_000_000_000);
+
+        assert!(matches!(
+            pyth.set_publish(&Pubkey::new_unique(), 1, 1),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_create_stale_feed_with_stale_by() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let clock = svm.get_sysvar::<Clock>();
+        let current_time = clock.unix_timestamp;
+
+        let mut pyth = Pyth::new(&mut svm);
+
+        // Create a feed that's already 5 minutes old
+        let stale_conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, current_time);
+        let feed = pyth.create_price_feed(stale_conf);
+
+        let feed_timestamp = pyth.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, current_time - 300);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.is_valid(&feed), Some(true));
+
+        pyth.set_status(&feed, PriceStatus::Halted).unwrap();
+        assert_eq!(pyth.is_valid(&feed), Some(false));
+
+        pyth.set_status(&feed, PriceStatus::Trading).unwrap();
+        pyth.set_price(&feed, 0, 0).unwrap();
+        assert_eq!(pyth.is_valid(&feed), Some(false));
+
+        assert_eq!(pyth.is_valid(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_set_price_type_and_account_type() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_type(&feed, 0).unwrap();
+        pyth.set_account_type(&feed, 2).unwrap();
+
+        let account = svm.get_account(&feed).unwrap();
+        let stored: PythPriceAccount = bytemuck::pod_read_unaligned(&account.data);
+        assert_eq!(stored.price_type, 0);
+        assert_eq!(stored.atype, 2);
+    }
+
+    #[test]
+    fn test_set_num_quoters() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.get_num_quoters(&feed), Some(1));
+
+        pyth.set_num_quoters(&feed, 0).unwrap();
+        assert_eq!(pyth.get_num_quoters(&feed), Some(0));
+        let account = PythPriceAccount::from_bytes(&pyth.svm.get_account(&feed).unwrap().data);
+        assert_eq!(account.unwrap().num_qt, 0);
+
+        // Later updates keep the quoter count
+        pyth.set_price_usd(&feed, 101.0, 0.1).unwrap();
+        assert_eq!(pyth.get_num_quoters(&feed), Some(0));
+        assert!(pyth.set_num_quoters(&Pubkey::new_unique(), 3).is_err());
+    }
+
+    #[test]
+    fn test_create_price_feed_at_pda() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"oracle", b"SOL"];
+        let (address, bump) =
+            pyth.create_price_feed_at_pda(seeds, &program_id, PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(
+            (address, bump),
+            Pubkey::find_program_address(seeds, &program_id)
+        );
+        assert!(pyth.get_price(&address).is_some());
+        assert!(svm.get_account(&address).is_some());
+    }
+
+    #[test]
+    fn test_update_count() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.update_count(&feed), Some(0));
+
+        pyth.set_price_usd(&feed, 110.0, 0.1).unwrap();
+        pyth.simulate_crash(&feed, 50.0).unwrap();
+        assert_eq!(pyth.update_count(&feed), Some(2));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_get_price_decimal() {
+        use std::str::FromStr;
+
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price(&feed, 10_012_345_678, 1_000_001).unwrap();
+
+        let (price, conf) = pyth.get_price_decimal(&feed).unwrap();
+        assert_eq!(price, crate::Decimal::from_str("100.12345678").unwrap());
+        assert_eq!(conf, crate::Decimal::from_str("0.01000001").unwrap());
+    }
+
+    #[test]
+    fn test_price_bounds_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.2));
+        let (lower, upper) = pyth.price_bounds_usd(&feed).unwrap();
+        assert!((lower - 99.8).abs() < 0.001);
+        assert!((upper - 100.2).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_conf_ratio() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 2.0));
+        assert!((pyth.conf_ratio(&feed).unwrap() - 0.02).abs() < 1e-12);
+
+        pyth.set_price(&feed, 0, 100).unwrap();
+        assert_eq!(pyth.conf_ratio(&feed), None);
+        assert_eq!(pyth.conf_ratio(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_set_price_sigma() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_sigma(&feed, 100.0, 5.0).unwrap();
+
+        let (price, conf) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 100.0).abs() < 1e-9);
+        assert!((conf - 5.0).abs() < 1e-9);
+
+        // A 1-sigma downside move lands at 95
+        let low = pyth.conservative_price_usd(&feed, 1.0, true).unwrap();
+        assert!((low - 95.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ema_divergence_pct() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.ema_divergence_pct(&feed), Some(0.0));
+
+        // Spot $100 with the EMA at $110 is 10% below the EMA
+        let diverged = pyth.create_price_feed(PriceConf::new_usd_with_ema(100.0, 0.1, 110.0, 0.1));
+        assert!((pyth.ema_divergence_pct(&diverged).unwrap() + 10.0).abs() < 1e-9);
+
+        pyth.set_price(&feed, 0, 100).unwrap();
+        assert_eq!(pyth.ema_divergence_pct(&feed), None);
+        assert_eq!(pyth.ema_divergence_pct(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_conservative_price_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 1.0));
+        let low = pyth.conservative_price_usd(&feed, 2.0, true).unwrap();
+        let high = pyth.conservative_price_usd(&feed, 2.0, false).unwrap();
+        assert!((low - 98.0).abs() < 1e-9);
+        assert!((high - 102.0).abs() < 1e-9);
+        assert_eq!(
+            pyth.conservative_price_usd(&Pubkey::new_unique(), 2.0, true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_try_create_price_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth
+            .try_create_price_feed(PriceConf::new_usd(100.0, 0.1))
+            .unwrap();
+        assert!(pyth.contains(&feed));
+
+        assert!(matches!(
+            pyth.try_create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(40)),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+
+        // The SVM refuses feed data at a sysvar address
+        let slot_hashes = Pubkey::from_str("SysvarS1otHashes111111111111111111111111111").unwrap();
+        let result = pyth.try_create_price_feed_at(slot_hashes, PriceConf::new_usd(100.0, 0.1));
+        assert!(matches!(result, Err(ShadowOracleError::SvmError(_))));
+        assert!(!pyth.contains(&slot_hashes));
+        assert_eq!(pyth.len(), 1);
+    }
+
+    #[test]
+    fn test_create_at_existing_address() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let address = Pubkey::new_unique();
+        pyth.create_price_feed_at(address, PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_usd(&address, 101.0, 0.1).unwrap();
+
+        // Creating again replaces the feed rather than aliasing it
+        pyth.create_price_feed_at(address, PriceConf::new_usd(200.0, 0.1));
+        assert_eq!(pyth.len(), 1);
+        assert_eq!(pyth.update_count(&address), Some(0));
+        let (price, _) = pyth.get_price_usd(&address).unwrap();
+        assert!((price - 200.0).abs() < 0.001);
+
+        // The fallible variant refuses and leaves the feed untouched
+        assert!(matches!(
+            pyth.try_create_price_feed_at(address, PriceConf::new_usd(300.0, 0.1)),
+            Err(ShadowOracleError::FeedAlreadyExists(_))
+        ));
+        let (price, _) = pyth.get_price_usd(&address).unwrap();
+        assert!((price - 200.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_stored_len() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.stored_len(&feed), Some(PythPriceAccount::SIZE));
+
+        let padded = pyth.create_price_feed_with_size(
+            PriceConf::new_usd(100.0, 0.1),
+            PythPriceAccount::SIZE + 64,
+        );
+        assert_eq!(pyth.stored_len(&padded), Some(PythPriceAccount::SIZE + 64));
+
+        // Reflects the account as stored, not the expected layout
+        let mut account = pyth.svm.get_account(&feed).unwrap();
+        account.data.truncate(64);
+        pyth.svm.set_account(feed, account).unwrap();
+        assert_eq!(pyth.stored_len(&feed), Some(64));
+
+        assert_eq!(pyth.stored_len(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_create_price_feed_owned_by() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let adapter = Pubkey::new_unique();
+        let owned = pyth.create_price_feed_owned_by(adapter, PriceConf::new_usd(100.0, 0.1));
+        let default = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.get_owner(&owned), Some(adapter));
+        assert_eq!(pyth.get_owner(&default), Some(pyth.program_id));
+
+        // The owner survives price updates
+        pyth.simulate_crash(&owned, 10.0).unwrap();
+        assert_eq!(svm.get_account(&owned).unwrap().owner, adapter);
+    }
+
+    #[test]
+    fn test_set_price_usd_tick_size() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_tick_size(0.01));
+        pyth.set_price_usd(&feed, 100.017, 0.123).unwrap();
+        // The price snaps to the tick; the confidence does not
+        assert_eq!(pyth.get_price(&feed), Some((10_002_000_000, 12_300_000)));
+
+        pyth.set_price_usd(&feed, 100.014, 0.1).unwrap();
+        assert_eq!(pyth.get_price(&feed).unwrap().0, 10_001_000_000);
+        assert_eq!(pyth.reading_as_conf(&feed).unwrap().tick_size, Some(0.01));
+    }
+
+    #[test]
+    fn test_set_price_usd_checked() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_quoted(1.0, 0.01, -2));
+        let result = pyth.set_price_usd_checked(&feed, 1.23456, 0.01);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(pyth.set_price_usd_checked(&feed, 1.23, 0.001).is_err());
+        assert_eq!(pyth.update_count(&feed), Some(0));
+
+        pyth.set_price_usd_checked(&feed, 1.23, 0.01).unwrap();
+        assert_eq!(pyth.get_price(&feed), Some((123, 1)));
+
+        // The lossy variant rounds to the nearest tick
+        pyth.set_price_usd(&feed, 1.23456, 0.01).unwrap();
+        assert_eq!(pyth.get_price(&feed), Some((123, 1)));
+
+        let usd = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_usd_checked(&usd, 100.1, 0.05).unwrap();
+        assert!(pyth
+            .set_price_usd_checked(&Pubkey::new_unique(), 1.0, 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_price_usd_checked_tick_size() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_tick_size(0.05));
+        assert!(matches!(
+            pyth.set_price_usd_checked(&feed, 100.02, 0.1),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(pyth.get_price(&feed), Some((10_000_000_000, 10_000_000)));
+
+        pyth.set_price_usd_checked(&feed, 100.05, 0.1).unwrap();
+        assert_eq!(pyth.get_price(&feed), Some((10_005_000_000, 10_000_000)));
+    }
+
+    #[test]
+    fn test_set_price_usd_expo() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        // Redenominate 1:200 and move to a finer exponent in one update
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_usd_expo(&feed, 0.5, 0.001, -10).unwrap();
+
+        assert_eq!(pyth.reading(&feed).unwrap().expo, -10);
+        assert_eq!(pyth.get_price(&feed), Some((5_000_000_000, 10_000_000)));
+        let (price, conf) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 0.5).abs() < 1e-12);
+        assert!((conf - 0.001).abs() < 1e-12);
+
+        // The EMA kept its USD value across the rescale: (9 * 100 + 0.5) / 10
+        let (ema, _) = pyth.get_ema_usd(&feed).unwrap();
+        assert!((ema - 90.05).abs() < 1e-9);
+        assert_eq!(pyth.update_count(&feed), Some(1));
+
+        assert!(matches!(
+            pyth.set_price_usd_expo(&feed, 0.5, 0.001, -19),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(pyth.reading(&feed).unwrap().expo, -10);
+    }
+
+    #[test]
+    fn test_set_price_usd_rejects_negative_confidence() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let result = pyth.set_price_usd(&feed, 100.0, -0.1);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(pyth.update_count(&feed), Some(0));
+    }
+
+    #[test]
+    fn test_dual_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let (feed, update) = pyth.create_dual_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(
+            pyth.get_price_update_from_svm(&update),
+            pyth.get_price_from_svm(&feed)
+        );
+
+        pyth.svm.warp_to_slot(50);
+        pyth.set_dual_price(&feed, 12_000_000_000, 20_000_000)
+            .unwrap();
+        assert_eq!(
+            pyth.get_price_update_from_svm(&update),
+            Some((12_000_000_000, 20_000_000))
+        );
+        assert_eq!(
+            pyth.get_price_update_from_svm(&update),
+            pyth.get_price_from_svm(&feed)
+        );
+
+        let account = pyth.svm.get_account(&update).unwrap();
+        assert_eq!(account.owner.to_string(), PYTH_RECEIVER_PROGRAM_ID);
+        assert_eq!(account.data.len(), PRICE_UPDATE_V2_SIZE);
+        let message = &account.data[PRICE_MESSAGE_OFFSET..];
+        assert_eq!(message[0..32], feed.to_bytes());
+        assert_eq!(message[48..52], (-8i32).to_le_bytes());
+        assert_eq!(message[84..92], 50u64.to_le_bytes());
+
+        // Plain feeds have no PriceUpdateV2 account
+        let plain = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert!(matches!(
+            pyth.set_dual_price(&plain, 1, 1),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+        assert_eq!(pyth.get_price_update_from_svm(&plain), None);
+    }
+
+    #[test]
+    fn test_product_account_symbol() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let product = pyth
+            .create_product_account(
+                &feed,
+                &[("asset_type", "Crypto"), ("symbol", "Crypto.SOL/USD")],
+            )
+            .unwrap();
+
+        assert_eq!(pyth.get_symbol(&product).as_deref(), Some("Crypto.SOL/USD"));
+
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        let account: PythPriceAccount = bytemuck::pod_read_unaligned(&data);
+        assert_eq!(account.prod, product.to_bytes());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_product_account_without_symbol() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let product = pyth
+            .create_product_account(&feed, &[("asset_type", "Crypto")])
+            .unwrap();
+
+        assert_eq!(pyth.get_symbol(&product), None);
+        assert_eq!(pyth.get_symbol(&feed), None);
+        assert!(pyth
+            .create_product_account(&Pubkey::new_unique(), &[])
+            .is_err());
+    }
 
     #[test]
-    fn test_create_price_feed() {
+    fn test_simulate_peg_jitter() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let a = pyth.create_price_feed(PriceConf::stablecoin());
+        let b = pyth.create_price_feed(PriceConf::stablecoin());
+        let c = pyth.create_price_feed(PriceConf::stablecoin());
+        let slot = pyth.svm.get_sysvar::<Clock>().slot;
+        pyth.simulate_peg_jitter(&a, 0.002, 50, 7).unwrap();
+        assert_eq!(pyth.svm.get_sysvar::<Clock>().slot, slot + 49);
+        pyth.simulate_peg_jitter(&b, 0.002, 50, 7).unwrap();
+        pyth.simulate_peg_jitter(&c, 0.002, 50, 8).unwrap();
+
+        let (price_a, price_b, price_c) = (
+            pyth.get_price_usd(&a).unwrap().0,
+            pyth.get_price_usd(&b).unwrap().0,
+            pyth.get_price_usd(&c).unwrap().0,
+        );
+        assert_eq!(price_a, price_b);
+        assert_ne!(price_a, price_c);
+        for price in [price_a, price_c] {
+            assert!((0.998..=1.002).contains(&price));
+        }
+
+        assert!(pyth
+            .simulate_peg_jitter(&Pubkey::new_unique(), 0.002, 5, 7)
+            .is_err());
+        let feed = pyth.create_price_feed(PriceConf::stablecoin());
+        assert!(matches!(
+            pyth.simulate_peg_jitter(&feed, 1.5, 5, 7),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+    }
+
+    #[test]
+    fn test_simulate_repeg() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::stablecoin());
+        pyth.simulate_depeg(&feed, 0.9).unwrap();
+        let (_, depeg_conf) = pyth.get_price_usd(&feed).unwrap();
+        let slot = pyth.get_slot(&feed).unwrap();
+
+        pyth.simulate_repeg(&feed, 4).unwrap();
+
+        let (price, conf) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 1.0).abs() < 0.001);
+        assert!(conf < depeg_conf);
+        assert_eq!(pyth.get_slot(&feed).unwrap(), slot + 3);
+        assert_eq!(pyth.update_count(&feed), Some(5));
+
+        // The previous step is one slot back, a quarter of the way from $1.00
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        let account: PythPriceAccount = bytemuck::pod_read_unaligned(&data);
+        assert_eq!(account.prev_slot, slot + 2);
+        assert!((account.prev_price as f64 / 1e8 - 0.975).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_labeled_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let labeled = pyth.create_price_feed_labeled("SOL/USD", PriceConf::new_usd(100.0, 0.1));
+        let plain = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(pyth.label(&labeled).as_deref(), Some("SOL/USD"));
+        assert_eq!(pyth.label(&plain), Some(plain.to_string()));
+        assert_eq!(pyth.label(&Pubkey::new_unique()), None);
+
+        let description = pyth.describe(&labeled).unwrap();
+        assert!(description.starts_with(&format!("SOL/USD ({labeled}): $100")));
+        assert!(pyth
+            .describe(&plain)
+            .unwrap()
+            .starts_with(&plain.to_string()));
+    }
+
+    #[test]
+    fn test_reading() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.5));
+        pyth.set_status(&feed, PriceStatus::Halted).unwrap();
+
+        let reading = pyth.reading(&feed).unwrap();
+        assert!((reading.price_usd - 100.0).abs() < 0.001);
+        assert!((reading.conf_usd - 0.5).abs() < 0.001);
+        assert_eq!(reading.expo, -8);
+        assert_eq!(reading.slot, pyth.get_slot(&feed).unwrap());
+        assert_eq!(reading.timestamp, pyth.get_timestamp(&feed).unwrap());
+        assert_eq!(reading.status, PriceStatus::Halted);
+        assert!(pyth.reading(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_is_tradeable() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut pyth = Pyth::new(&mut svm);
 
         let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.is_tradeable(&feed), Some(true));
 
-        let (price, conf) = pyth.get_price(&feed).unwrap();
-        assert_eq!(price, 10000000000);
-        assert_eq!(conf, 10000000);
+        pyth.set_status(&feed, PriceStatus::Halted).unwrap();
+        assert_eq!(pyth.is_tradeable(&feed), Some(false));
+        pyth.set_status(&feed, PriceStatus::Trading).unwrap();
+
+        // Too many slots behind the clock
+        pyth.svm.warp_to_slot(26);
+        assert_eq!(pyth.is_tradeable(&feed), Some(false));
+        pyth.set_price_usd(&feed, 100.0, 0.1).unwrap();
+        assert_eq!(pyth.is_tradeable(&feed), Some(true));
+
+        // Too old
+        pyth.make_stale(&feed, 61).unwrap();
+        assert_eq!(pyth.is_tradeable(&feed), Some(false));
+
+        pyth.set_price(&feed, 0, 0).unwrap();
+        assert_eq!(pyth.is_tradeable(&feed), Some(false));
+        assert_eq!(pyth.is_tradeable(&Pubkey::new_unique()), None);
     }
 
     #[test]
-    fn test_update_price() {
+    fn test_reading_as_conf() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut pyth = Pyth::new(&mut svm);
 
         let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        pyth.set_price_usd(&feed, 150.0, 0.2).unwrap();
+        pyth.set_price_usd(&feed, 90.0, 0.2).unwrap();
+        pyth.set_status(&feed, PriceStatus::Halted).unwrap();
+
+        let conf = pyth.reading_as_conf(&feed).unwrap();
+        assert_eq!(conf.price, 9_000_000_000);
+        assert_eq!(conf.conf, 20_000_000);
+        assert_eq!(conf.expo, -8);
+        assert_eq!(conf.status, PriceStatus::Halted);
+        assert_eq!(conf.publish_time, pyth.get_timestamp(&feed));
+
+        let copy = pyth.create_price_feed(conf.clone());
+        assert_eq!(
+            pyth.reading_as_conf(&copy).unwrap().ema_price,
+            conf.ema_price
+        );
+        assert_eq!(pyth.get_price(&copy), pyth.get_price(&feed));
+        assert!(pyth.reading_as_conf(&Pubkey::new_unique()).is_none());
+    }
 
-        let (price, _) = pyth.get_price_usd(&feed).unwrap();
-        assert!((price - 150.0).abs() < 0.001);
+    #[test]
+    fn test_simulate_outage() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let expected = pyth.get_price_from_svm(&feed);
+        assert!(expected.is_some());
+
+        pyth.simulate_outage(&feed).unwrap();
+        assert_eq!(pyth.get_price_from_svm(&feed), None);
+        let account = pyth.svm.get_account(&feed).unwrap();
+        assert_eq!(account.owner, Pubkey::default());
+        assert!(account.data.iter().all(|b| *b == 0));
+
+        pyth.restore_feed(&feed).unwrap();
+        assert_eq!(pyth.get_price_from_svm(&feed), expected);
+
+        let missing = Pubkey::new_unique();
+        assert!(matches!(
+            pyth.simulate_outage(&missing),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+        assert!(pyth.restore_feed(&missing).is_err());
     }
 
     #[test]
-    fn test_standard_feeds() {
+    fn test_conf_usd() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut pyth = Pyth::new(&mut svm);
 
-        let feeds = pyth.create_standard_feeds();
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.25));
+        assert_eq!(pyth.conf_usd(&feed), Some(0.25));
+        pyth.set_price_usd(&feed, 101.0, 0.5).unwrap();
+        assert_eq!(pyth.conf_usd(&feed), Some(0.5));
+        assert_eq!(pyth.conf_usd(&Pubkey::new_unique()), None);
+    }
 
-        let (sol_price, _) = pyth.get_price_usd(&feeds.sol).unwrap();
-        assert!((sol_price - 100.0).abs() < 0.001);
+    #[test]
+    fn test_conf_bps() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
 
-        let (usdc_price, _) = pyth.get_price_usd(&feeds.usdc).unwrap();
-        assert!((usdc_price - 1.0).abs() < 0.001);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.25));
+        assert_eq!(pyth.conf_bps(&feed), Some(25));
+        pyth.set_price_usd(&feed, 1.0, 0.00015).unwrap();
+        assert_eq!(pyth.conf_bps(&feed), Some(2)); // 1.5 bps rounds up
+
+        pyth.set_price(&feed, 0, 100).unwrap();
+        assert_eq!(pyth.conf_bps(&feed), None);
+        assert_eq!(pyth.conf_bps(&Pubkey::new_unique()), None);
     }
 
     #[test]
-    fn test_simulate_crash() {
+    fn test_make_future() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+
         let mut pyth = Pyth::new(&mut svm);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.make_future(&feed, 120).unwrap();
+
+        let now = pyth.svm.get_sysvar::<Clock>().unix_timestamp;
+        let timestamp = pyth.get_timestamp(&feed).unwrap();
+        assert!(timestamp > now);
+        assert_eq!(timestamp, 1_700_000_120);
+        assert!(pyth.make_future(&Pubkey::new_unique(), 120).is_err());
+    }
+
+    #[test]
+    fn test_set_publish_time_relative() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
 
+        let mut pyth = Pyth::new(&mut svm);
         let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        pyth.simulate_crash(&feed, 50.0).unwrap();
 
-        let (price, _) = pyth.get_price_usd(&feed).unwrap();
-        assert!((price - 50.0).abs() < 0.001);
+        pyth.set_publish_time_relative(&feed, 3).unwrap();
+        assert_eq!(pyth.get_timestamp(&feed), Some(1_700_000_003));
+        pyth.set_publish_time_relative(&feed, -2).unwrap();
+        assert_eq!(pyth.get_timestamp(&feed), Some(1_699_999_998));
+        assert_eq!(
+            pyth.get_price_from_svm(&feed),
+            Some((10_000_000_000, 10_000_000))
+        );
     }
 
     #[test]
-    fn test_simulate_depeg() {
+    fn test_account_hash() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+
         let mut pyth = Pyth::new(&mut svm);
+        let first = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.svm.warp_to_slot(50);
+        let mut clock = pyth.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 30;
+        pyth.svm.set_sysvar(&clock);
+        let second = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        assert_ne!(pyth.account_hash(&first), pyth.account_hash(&second));
+        let stable = pyth.account_hash_stable(&first).unwrap();
+        assert_eq!(pyth.account_hash_stable(&second), Some(stable));
+
+        pyth.set_price_usd(&second, 101.0, 0.1).unwrap();
+        assert_ne!(pyth.account_hash_stable(&second), Some(stable));
+        assert_eq!(pyth.account_hash(&Pubkey::new_unique()), None);
+        assert_eq!(pyth.account_hash_stable(&Pubkey::new_unique()), None);
+    }
 
-        let feed = pyth.create_price_feed(PriceConf::stablecoin());
-        pyth.simulate_depeg(&feed, 0.95).unwrap();
+    #[test]
+    fn test_make_all_stale() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let current_time = svm.get_sysvar::<Clock>().unix_timestamp;
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feeds: Vec<Pubkey> = (0..3)
+            .map(|_| pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1)))
+            .collect();
+        pyth.make_all_stale(600).unwrap();
+
+        for feed in &feeds {
+            assert_eq!(pyth.get_timestamp(feed), Some(current_time - 600));
+        }
+    }
+
+    #[test]
+    fn test_create_invalid_magic_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_invalid_magic_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.get_price_usd(&feed), Some((100.0, 0.1)));
+        assert_eq!(pyth.get_price_from_svm(&feed), None);
+
+        // Updates keep the bad magic
+        pyth.set_price_usd(&feed, 110.0, 0.1).unwrap();
+        assert_eq!(pyth.get_price_usd(&feed), Some((110.0, 0.1)));
+        assert_eq!(pyth.get_price_from_svm(&feed), None);
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[0..4], (!PYTH_MAGIC).to_le_bytes());
+    }
+
+    #[test]
+    fn test_create_price_feed_with_size() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let exact = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let padded = pyth.create_price_feed_with_size(
+            PriceConf::new_usd(100.0, 0.1),
+            PythPriceAccount::SIZE + 64,
+        );
+        let truncated = pyth.create_price_feed_with_size(PriceConf::new_usd(100.0, 0.1), 100);
+
+        assert_eq!(
+            pyth.svm.get_account(&exact).unwrap().data.len(),
+            PythPriceAccount::SIZE
+        );
+        assert_eq!(
+            pyth.svm.get_account(&padded).unwrap().data.len(),
+            PythPriceAccount::SIZE + 64
+        );
+        assert_eq!(pyth.svm.get_account(&truncated).unwrap().data.len(), 100);
+
+        // Updates keep the configured length
+        pyth.set_price_usd(&padded, 110.0, 0.1).unwrap();
+        let data = pyth.svm.get_account(&padded).unwrap().data;
+        assert_eq!(data.len(), PythPriceAccount::SIZE + 64);
+        let account: PythPriceAccount =
+            bytemuck::pod_read_unaligned(&data[..PythPriceAccount::SIZE]);
+        assert_eq!(account.agg.price, 11_000_000_000);
+        assert!(data[PythPriceAccount::SIZE..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(0.0, 0.0));
+
+        assert!(pyth.contains(&feed));
+        assert!(!pyth.contains(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_created_and_last_update_slot() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        svm.warp_to_slot(100);
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.created_slot(&feed), Some(100));
+        assert_eq!(pyth.last_update_slot(&feed), Some(100));
+
+        pyth.svm.warp_to_slot(250);
+        pyth.set_price_usd(&feed, 110.0, 0.1).unwrap();
+        assert_eq!(pyth.created_slot(&feed), Some(100));
+        assert_eq!(pyth.last_update_slot(&feed), Some(250));
+    }
+
+    #[test]
+    fn test_len() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+        assert!(pyth.is_empty());
+
+        pyth.create_standard_feeds();
+        assert_eq!(pyth.len(), 5);
+        assert!(!pyth.is_empty());
+    }
+
+    #[test]
+    fn test_create_feed_with_diverging_ema() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd_with_ema(100.0, 0.05, 110.0, 0.08));
 
         let (price, _) = pyth.get_price_usd(&feed).unwrap();
-        assert!((price - 0.95).abs() < 0.001);
+        let (ema_price, ema_conf) = pyth.get_ema_usd(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+        assert!((ema_price - 110.0).abs() < 0.001);
+        assert!((ema_conf - 0.08).abs() < 0.001);
+
+        // Without an explicit EMA, it starts at spot
+        let plain = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.05));
+        let (ema_price, _) = pyth.get_ema_usd(&plain).unwrap();
+        assert!((ema_price - 100.0).abs() < 0.001);
     }
 
     #[test]
-    fn test_timestamp_uses_svm_clock() {
+    #[should_panic(expected = "decimals 40 exceeds")]
+    fn test_create_rejects_out_of_range_decimals() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
 
-        let initial_clock = svm.get_sysvar::<Clock>();
-        let initial_timestamp = initial_clock.unix_timestamp;
+        pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(40));
+    }
+
+    #[test]
+    fn test_get_price_from_svm() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price(&feed, 12_345_000_000, 6_000_000).unwrap();
+        assert_eq!(
+            pyth.get_price_from_svm(&feed),
+            Some((12_345_000_000, 6_000_000))
+        );
+        assert_eq!(pyth.get_price_from_svm(&feed), pyth.get_price(&feed));
+
+        // Padded accounts still deserialize
+        let padded = pyth.create_price_feed_with_size(PriceConf::new_usd(50.0, 0.1), 4096);
+        assert_eq!(pyth.get_price_from_svm(&padded), pyth.get_price(&padded));
+
+        assert_eq!(pyth.get_price_from_svm(&Pubkey::new_unique()), None);
+
+        // Accounts owned by another program fail the owner check
+        let foreign =
+            pyth.create_price_feed_owned_by(Pubkey::new_unique(), PriceConf::new_usd(100.0, 0.1));
+        assert!(pyth.get_price(&foreign).is_some());
+        assert_eq!(pyth.get_price_from_svm(&foreign), None);
+    }
+
+    #[test]
+    fn test_simulate_with() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm).with_history();
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.simulate_with(&feed, 10, 14, |s| 100.0 + s as f64)
+            .unwrap();
+
+        let history = pyth.history(&feed).unwrap();
+        assert_eq!(history.len(), 6);
+        for (point, slot) in history[1..].iter().zip(10..=14) {
+            assert_eq!(point.slot, slot);
+            assert!((point.price_usd - (100.0 + slot as f64)).abs() < 0.001);
+        }
+        assert_eq!(pyth.get_slot(&feed), Some(14));
+        assert_eq!(pyth.svm.get_sysvar::<Clock>().slot, 14);
+    }
 
+    #[test]
+    fn test_simulate_price_impact() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut pyth = Pyth::new(&mut svm);
+
         let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
 
-        let feed_timestamp = pyth.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, initial_timestamp);
+        // A $50k buy into $1M of liquidity moves the price up 5%
+        pyth.simulate_price_impact(&feed, 50_000.0, 1_000_000.0)
+            .unwrap();
+        let (price, _) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 105.0).abs() < 0.001);
+
+        // A sell of the same size moves it down 5% from there
+        pyth.simulate_price_impact(&feed, -50_000.0, 1_000_000.0)
+            .unwrap();
+        let (price, _) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 99.75).abs() < 0.001);
+
+        assert!(matches!(
+            pyth.simulate_price_impact(&feed, 1.0, 0.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(matches!(
+            pyth.simulate_price_impact(&feed, f64::NAN, 1_000_000.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(matches!(
+            pyth.simulate_price_impact(&feed, f64::INFINITY, 1_000_000.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
     }
 
     #[test]
-    fn test_slot_uses_svm_clock() {
+    fn test_simulate_range() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm).with_history();
 
-        let initial_clock = svm.get_sysvar::<Clock>();
-        let initial_slot = initial_clock.slot;
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.simulate_range(&feed, 90.0, 110.0, 2).unwrap();
+
+        let history = pyth.history(&feed).unwrap();
+        let prices: Vec<f64> = history.iter().map(|p| p.price_usd).collect();
+        let slots: Vec<u64> = history.iter().map(|p| p.slot).collect();
+        assert_eq!(prices.len(), 7);
+        for (price, expected) in prices
+            .iter()
+            .zip([100.0, 95.0, 90.0, 100.0, 110.0, 105.0, 100.0])
+        {
+            assert!((price - expected).abs() < 0.001);
+        }
+        assert_eq!(slots, vec![0, 0, 1, 2, 3, 4, 5]);
+
+        assert!(matches!(
+            pyth.simulate_range(&Pubkey::new_unique(), 90.0, 110.0, 2),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm).with_history();
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.svm.warp_to_slot(10);
+        pyth.set_price_usd(&feed, 90.0, 0.1).unwrap();
+        pyth.svm.warp_to_slot(20);
+        pyth.set_price_usd(&feed, 95.0, 0.1).unwrap();
+
+        let history = pyth.history(&feed).unwrap();
+        let prices: Vec<f64> = history.iter().map(|p| p.price_usd).collect();
+        let slots: Vec<u64> = history.iter().map(|p| p.slot).collect();
+        assert_eq!(prices.len(), 3);
+        for (price, expected) in prices.iter().zip([100.0, 90.0, 95.0]) {
+            assert!((price - expected).abs() < 0.001);
+        }
+        assert_eq!(slots, vec![0, 10, 20]);
+        assert_eq!(pyth.history(&Pubkey::new_unique()), None);
+    }
 
+    #[test]
+    fn test_set_price_slot_follows_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut pyth = Pyth::new(&mut svm);
+
         let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.svm.warp_to_slot(100);
+        pyth.set_price_usd(&feed, 101.0, 0.1).unwrap();
+
+        assert_eq!(pyth.get_slot(&feed), Some(100));
+        let data = pyth.svm.get_account(&feed).unwrap().data;
+        let account = PythPriceAccount::from_bytes(&data).unwrap();
+        assert_eq!(account.valid_slot, 100);
+        assert_eq!(account.agg.pub_slot, 100);
+    }
 
-        let feed_slot = pyth.get_slot(&feed).unwrap();
-        assert_eq!(feed_slot, initial_slot);
+    #[test]
+    fn test_history_off_by_default() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_usd(&feed, 90.0, 0.1).unwrap();
+
+        assert_eq!(pyth.history(&feed), Some(&[][..]));
     }
 
     #[test]
-    fn test_make_stale() {
+    fn test_config_for() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm).with_template(PriceConf::default().with_decimals(6));
 
-        let clock = svm.get_sysvar::<Clock>();
-        let current_time = clock.unix_timestamp;
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.set_price_usd(&feed, 90.0, 0.1).unwrap();
+
+        let config = pyth.config_for(&feed).unwrap();
+        assert_eq!(config.price, 10_000_000_000);
+        assert_eq!(config.decimals, 6);
+        assert!(pyth.config_for(&Pubkey::new_unique()).is_none());
+    }
 
+    #[test]
+    fn test_program_id_default() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut pyth = Pyth::new(&mut svm);
+
+        assert_eq!(Pyth::program_id_default().to_string(), PYTH_PROGRAM_ID);
         let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(pyth.get_owner(&feed), Some(Pyth::program_id_default()));
+    }
 
-        // Make the feed 5 minutes stale
-        pyth.make_stale(&feed, 300).unwrap();
+    #[test]
+    fn test_simulate_crash_and_halt() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
 
-        let feed_timestamp = pyth.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, current_time - 300);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.simulate_crash_and_halt(&feed, 40.0).unwrap();
+
+        let (price, conf) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 60.0).abs() < 0.001);
+        assert!((conf - 0.5).abs() < 0.001);
+        assert_eq!(pyth.reading(&feed).unwrap().status, PriceStatus::Halted);
+        assert_eq!(pyth.is_tradeable(&feed), Some(false));
     }
 
     #[test]
-    fn test_create_stale_feed_with_stale_by() {
+    fn test_recover_baseline() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
 
-        let clock = svm.get_sysvar::<Clock>();
-        let current_time = clock.unix_timestamp;
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert!(matches!(
+            pyth.recover_baseline(&feed),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+
+        pyth.capture_baseline(&feed).unwrap();
+        pyth.simulate_crash_and_halt(&feed, 40.0).unwrap();
+        pyth.recover_baseline(&feed).unwrap();
+
+        assert_eq!(pyth.get_price(&feed), Some((10_000_000_000, 10_000_000)));
+        assert_eq!(pyth.reading(&feed).unwrap().status, PriceStatus::Trading);
+        assert_eq!(pyth.get_price_from_svm(&feed), pyth.get_price(&feed));
+        assert!(pyth.capture_baseline(&Pubkey::new_unique()).is_err());
+    }
 
+    #[test]
+    fn test_simulate_crash_realistic() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let current_time = svm.get_sysvar::<Clock>().unix_timestamp;
         let mut pyth = Pyth::new(&mut svm);
 
-        // Create a feed that's already 5 minutes old
-        let stale_conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, current_time);
-        let feed = pyth.create_price_feed(stale_conf);
+        let feed = pyth.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        pyth.simulate_crash_realistic(&feed, 30.0, 45).unwrap();
 
-        let feed_timestamp = pyth.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, current_time - 300);
+        let (price, conf) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 70.0).abs() < 0.001);
+        assert!((conf - 0.5).abs() < 0.001);
+        assert_eq!(pyth.get_timestamp(&feed), Some(current_time - 45));
+    }
+
+    #[test]
+    fn test_create_standard_feeds_at_mainnet() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feeds = pyth.create_standard_feeds_at_mainnet();
+        assert_eq!(feeds.sol, crate::feeds::pyth::sol_usd());
+        assert_eq!(feeds.btc, crate::feeds::pyth::btc_usd());
+        assert_eq!(feeds.eth, crate::feeds::pyth::eth_usd());
+        assert_eq!(feeds.usdc, crate::feeds::pyth::usdc_usd());
+        assert_eq!(feeds.usdt, crate::feeds::pyth::usdt_usd());
+        assert_eq!(pyth.len(), 5);
+
+        let (price, _) = pyth.get_price_usd(&feeds.btc).unwrap();
+        assert!((price - 43000.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_tick_ema() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut pyth = Pyth::new(&mut svm);
+
+        let feed = pyth.create_price_feed(PriceConf::new_usd_with_ema(100.0, 0.1, 120.0, 0.1));
+
+        let mut gap = 20.0;
+        for slot in 1..=10 {
+            pyth.svm.warp_to_slot(slot);
+            pyth.tick_ema(&feed).unwrap();
+
+            let (ema, _) = pyth.get_ema_usd(&feed).unwrap();
+            assert!(ema - 100.0 < gap);
+            gap = ema - 100.0;
+            assert_eq!(pyth.get_slot(&feed), Some(slot));
+        }
+        // Ten steps of 10% smoothing leave about 0.9^10 of the gap
+        assert!((gap - 20.0 * 0.9f64.powi(10)).abs() < 0.001);
+
+        let (price, _) = pyth.get_price_usd(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
     }
 }
@@ -2,7 +2,8 @@
 //!
 //! Mock Switchboard V2 aggregator feeds for LiteSVM testing.
 
-use crate::{PriceConf, ShadowOracleError, StandardFeeds};
+use super::{ProviderState, SplitMix64};
+use crate::{PriceConf, PricePoint, PriceReading, PriceStatus, ShadowOracleError, StandardFeeds};
 use litesvm::LiteSVM;
 use solana_account::Account;
 use solana_clock::Clock;
@@ -10,6 +11,7 @@ use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 use solana_signer::Signer;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::str::FromStr;
 
 /// Switchboard V2 Program ID (mainnet)
@@ -21,37 +23,116 @@ pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: &str = "SBondMDrcV3K4kxZR1HNVT7osZxA
 /// Discriminator for AggregatorAccountData
 const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
 
+/// Offset of `latest_confirmed_round` in AggregatorAccountData
+const ROUND_OFFSET: usize = 1144;
+/// Offset of the round's result SwitchboardDecimal; std_deviation follows 32 bytes later
+const RESULT_OFFSET: usize = ROUND_OFFSET + 25;
+/// Size of an AggregatorRound: header, four decimals, then 16 oracle keys,
+/// medians, payouts and fulfilment flags
+const ROUND_SIZE: usize = 25 + 4 * 32 + 16 * (32 + 32 + 8 + 1 + 1);
+/// Offset of `current_round`, which directly follows `latest_confirmed_round`
+const CURRENT_ROUND_OFFSET: usize = ROUND_OFFSET + ROUND_SIZE;
+/// Successful oracle responses recorded in each confirmed round
+const NUM_ORACLE_RESPONSES: u32 = 3;
+/// Open slot and timestamp of the latest confirmed and current rounds, which
+/// differ between runs
+pub(crate) const VOLATILE_FIELDS: [Range<usize>; 2] = [
+    ROUND_OFFSET + 9..ROUND_OFFSET + 25,
+    CURRENT_ROUND_OFFSET + 9..CURRENT_ROUND_OFFSET + 25,
+];
+
 /// Switchboard aggregator data - manually serialized to avoid Pod issues
 #[derive(Debug, Clone)]
 struct SwitchboardAggregator {
     price: f64,
     std_deviation: f64,
+    /// Smallest oracle response in the latest round
+    min_response: f64,
+    /// Largest oracle response in the latest round
+    max_response: f64,
     decimals: u8,
     slot: u64,
     timestamp: i64,
     round_id: u32,
+    /// Program that owns the feed account
+    owner: Pubkey,
+    /// Number of price updates applied since creation
+    update_count: u64,
+    /// Slot at which the feed was created
+    created_slot: u64,
+    /// Slot of the last `set_price` call, or the creation slot if never updated
+    last_update_slot: u64,
+    /// Config the feed was created from, after applying the provider template
+    config: PriceConf,
+    /// Optional name shown by `describe` and in error messages
+    label: Option<String>,
+    /// Prices recorded while history is enabled, oldest first
+    price_log: Vec<PricePoint>,
+    num_success: u32,
+    /// Open slot and timestamp of a round awaiting confirmation, if any
+    open_round: Option<(u64, i64)>,
+    /// Price, std deviation and timestamp of the round before the latest one
+    previous_round: Option<(f64, f64, i64)>,
+    /// Whether the account holds exactly the rent-exempt minimum instead of 1 SOL
+    rent_exempt: bool,
+    /// Price, std deviation and `num_success` saved by `capture_baseline`
+    baseline: Option<(f64, f64, u32)>,
 }
 
 impl SwitchboardAggregator {
-    fn from_conf(conf: &PriceConf, clock: &Clock) -> Self {
+    fn from_conf(conf: &PriceConf, clock: &Clock, owner: Pubkey) -> Self {
         let now = conf.publish_time.unwrap_or(clock.unix_timestamp);
 
         Self {
             price: conf.price_usd(),
             std_deviation: conf.conf_usd(),
+            min_response: conf.price_usd(),
+            max_response: conf.price_usd(),
             decimals: conf.decimals,
             slot: clock.slot,
             timestamp: now,
             round_id: 1,
+            owner,
+            update_count: 0,
+            created_slot: clock.slot,
+            last_update_slot: clock.slot,
+            config: conf.clone(),
+            label: None,
+            price_log: Vec::new(),
+            num_success: NUM_ORACLE_RESPONSES,
+            open_round: None,
+            previous_round: None,
+            rent_exempt: false,
+            baseline: None,
         }
     }
 
     fn set_price(&mut self, price: f64, std_dev: f64, clock: &Clock) {
+        self.previous_round = Some((self.price, self.std_deviation, self.timestamp));
         self.price = price;
         self.std_deviation = std_dev;
+        self.min_response = price;
+        self.max_response = price;
         self.slot = clock.slot;
         self.round_id += 1;
+        self.update_count += 1;
         self.timestamp = clock.unix_timestamp;
+        self.num_success = NUM_ORACLE_RESPONSES;
+        self.open_round = None;
+    }
+
+    /// Update the latest result in place, keeping its slot, timestamp and round
+    fn overwrite_price(&mut self, price: f64, std_dev: f64) {
+        self.price = price;
+        self.std_deviation = std_dev;
+        self.min_response = price;
+        self.max_response = price;
+        self.update_count += 1;
+    }
+
+    /// Scale a value into a SwitchboardDecimal mantissa (scale = decimals)
+    fn mantissa(&self, value: f64) -> i128 {
+        (value * 10f64.powi(self.decimals as i32)).round() as i128
     }
 
     /// Serialize to Switchboard-compatible format
@@ -71,10 +152,10 @@ impl SwitchboardAggregator {
         // then round_open_slot (8), round_open_timestamp (8)
         // then result as SwitchboardDecimal (mantissa i128 = 16, scale u32 = 4, padding = 12)
         // Total SwitchboardDecimal = 32 bytes
-        let round_offset = 1144;
+        let round_offset = ROUND_OFFSET;
 
         // num_success
-        data[round_offset..round_offset + 4].copy_from_slice(&3u32.to_le_bytes());
+        data[round_offset..round_offset + 4].copy_from_slice(&self.num_success.to_le_bytes());
         // num_error
         data[round_offset + 4..round_offset + 8].copy_from_slice(&0u32.to_le_bytes());
         // is_closed
@@ -87,37 +168,167 @@ impl SwitchboardAggregator {
         // Result as SwitchboardDecimal
         // mantissa = price * 10^scale
         let scale = self.decimals as u32;
-        let multiplier = 10f64.powi(scale as i32);
-        let mantissa = (self.price * multiplier) as i128;
+        let mantissa = self.mantissa(self.price);
 
-        let result_offset = round_offset + 25;
+        let result_offset = RESULT_OFFSET;
         data[result_offset..result_offset + 16].copy_from_slice(&mantissa.to_le_bytes());
         data[result_offset + 16..result_offset + 20].copy_from_slice(&scale.to_le_bytes());
 
         // std_deviation as SwitchboardDecimal
-        let std_mantissa = (self.std_deviation * multiplier) as i128;
+        let std_mantissa = self.mantissa(self.std_deviation);
         let std_offset = result_offset + 32;
         data[std_offset..std_offset + 16].copy_from_slice(&std_mantissa.to_le_bytes());
         data[std_offset + 16..std_offset + 20].copy_from_slice(&scale.to_le_bytes());
 
+        // min_response and max_response as SwitchboardDecimals
+        for (i, value) in [self.min_response, self.max_response]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = std_offset + 32 * (i + 1);
+            data[offset..offset + 16].copy_from_slice(&self.mantissa(value).to_le_bytes());
+            data[offset + 16..offset + 20].copy_from_slice(&scale.to_le_bytes());
+        }
+
+        // current_round: a pending round while one is open, otherwise a copy
+        // of latest_confirmed_round
+        match self.open_round {
+            Some((slot, timestamp)) => {
+                // num_success, num_error, is_closed and the result stay zero
+                let current = CURRENT_ROUND_OFFSET;
+                data[current + 9..current + 17].copy_from_slice(&slot.to_le_bytes());
+                data[current + 17..current + 25].copy_from_slice(&timestamp.to_le_bytes());
+            }
+            None => data.copy_within(
+                ROUND_OFFSET..ROUND_OFFSET + ROUND_SIZE,
+                CURRENT_ROUND_OFFSET,
+            ),
+        }
+
         data
     }
+
+    /// Read the result and std deviation back from serialized account data
+    fn read_result(data: &[u8]) -> Option<(f64, f64)> {
+        if data.get(..8)? != AGGREGATOR_DISCRIMINATOR {
+            return None;
+        }
+        let decimal = |offset: usize| -> Option<f64> {
+            let mantissa = i128::from_le_bytes(data.get(offset..offset + 16)?.try_into().ok()?);
+            let scale = u32::from_le_bytes(data.get(offset + 16..offset + 20)?.try_into().ok()?);
+            Some(mantissa as f64 / 10f64.powi(scale as i32))
+        };
+        Some((decimal(RESULT_OFFSET)?, decimal(RESULT_OFFSET + 32)?))
+    }
+
+    /// Append the current price to the history log
+    fn record_price(&mut self) {
+        self.price_log.push(PricePoint {
+            slot: self.slot,
+            timestamp: self.timestamp,
+            price_usd: self.price,
+            conf_usd: self.std_deviation,
+        });
+    }
+}
+
+/// Aggregator bytes for a config at the given clock, as a new feed writes them
+pub(crate) fn aggregator_bytes(conf: &PriceConf, clock: &Clock) -> Vec<u8> {
+    SwitchboardAggregator::from_conf(conf, clock, Pubkey::default()).to_bytes()
+}
+
+/// Feeds and settings of a provider, kept across handles by [`crate::ShadowOracle`]
+#[derive(Default)]
+pub(crate) struct SwitchboardState {
+    price_feeds: HashMap<Pubkey, SwitchboardAggregator>,
+    record_history: bool,
+    slot_frozen: bool,
+}
+
+impl SwitchboardState {
+    /// Whether `feed` is tracked
+    pub(crate) fn contains(&self, feed: &Pubkey) -> bool {
+        self.price_feeds.contains_key(feed)
+    }
+
+    /// Number of tracked feeds
+    pub(crate) fn len(&self) -> usize {
+        self.price_feeds.len()
+    }
+
+    /// Addresses of all tracked feeds, in no particular order
+    pub(crate) fn feeds(&self) -> impl Iterator<Item = &Pubkey> {
+        self.price_feeds.keys()
+    }
+
+    /// Label attached to a feed at creation, if any
+    pub(crate) fn label(&self, feed: &Pubkey) -> Option<&str> {
+        self.price_feeds.get(feed)?.label.as_deref()
+    }
+
+    /// Tick size the feed's config quantizes USD updates to, if any
+    pub(crate) fn tick_size(&self, feed: &Pubkey) -> Option<f64> {
+        self.price_feeds.get(feed)?.config.tick_size
+    }
+
+    /// Replace a tracked feed's label; does nothing for untracked feeds
+    pub(crate) fn set_label(&mut self, feed: &Pubkey, label: Option<String>) {
+        if let Some(state) = self.price_feeds.get_mut(feed) {
+            state.label = label;
+        }
+    }
+
+    /// Current price and std deviation of a feed in USD
+    pub(crate) fn price_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.price_feeds
+            .get(feed)
+            .map(|a| (a.price, a.std_deviation))
+    }
+
+    /// A feed's current state as a `PriceConf`, see [`Switchboard::reading_as_conf`]
+    pub(crate) fn reading_as_conf(&self, feed: &Pubkey) -> Option<PriceConf> {
+        let account = self.price_feeds.get(feed)?;
+        let scale = 10f64.powi(account.decimals as i32);
+        Some(PriceConf {
+            price: (account.price * scale).round() as i64,
+            conf: (account.std_deviation * scale).round() as u64,
+            expo: -(account.decimals as i32),
+            publish_time: Some(account.timestamp),
+            decimals: account.decimals,
+            tick_size: account.config.tick_size,
+            explicit_expo: true,
+            explicit_decimals: true,
+            ..Default::default()
+        })
+    }
 }
 
 /// Switchboard oracle provider for LiteSVM
 pub struct Switchboard<'a> {
     svm: &'a mut LiteSVM,
-    price_feeds: HashMap<Pubkey, SwitchboardAggregator>,
+    state: ProviderState<'a, SwitchboardState>,
     program_id: Pubkey,
+    template: PriceConf,
 }
 
 impl<'a> Switchboard<'a> {
+    /// The Switchboard V2 program ID ([`SWITCHBOARD_PROGRAM_ID`]) as a `Pubkey`
+    pub fn program_id_default() -> Pubkey {
+        Pubkey::from_str(SWITCHBOARD_PROGRAM_ID).unwrap()
+    }
+
+    /// Anchor discriminator the mock writes at the start of every aggregator account
+    pub fn discriminator() -> [u8; 8] {
+        AGGREGATOR_DISCRIMINATOR
+    }
+
     /// Create a new Switchboard provider
     pub fn new(svm: &'a mut LiteSVM) -> Self {
         Self {
             svm,
-            price_feeds: HashMap::new(),
-            program_id: Pubkey::from_str(SWITCHBOARD_PROGRAM_ID).unwrap(),
+            state: ProviderState::Owned(SwitchboardState::default()),
+            program_id: Self::program_id_default(),
+            template: PriceConf::default(),
         }
     }
 
@@ -125,67 +336,316 @@ impl<'a> Switchboard<'a> {
     pub fn with_program_id(svm: &'a mut LiteSVM, program_id: Pubkey) -> Self {
         Self {
             svm,
-            price_feeds: HashMap::new(),
+            state: ProviderState::Owned(SwitchboardState::default()),
             program_id,
+            template: PriceConf::default(),
+        }
+    }
+
+    /// Create a provider operating on state owned elsewhere
+    pub(crate) fn from_state(svm: &'a mut LiteSVM, state: &'a mut SwitchboardState) -> Self {
+        Self {
+            svm,
+            state: ProviderState::Borrowed(state),
+            program_id: Self::program_id_default(),
+            template: PriceConf::default(),
         }
     }
 
+    /// Use `template` for the exponent and decimals of new feeds
+    ///
+    /// Applies to feeds whose `PriceConf` does not set `expo`/`decimals`
+    /// explicitly, such as [`PriceConf::new_usd`]; per-feed settings from
+    /// `new_quoted`, `with_expo` or `with_decimals` still win.
+    pub fn with_template(mut self, template: PriceConf) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Record every price set on this provider's feeds, see [`history`](Self::history)
+    ///
+    /// Off by default to avoid unbounded memory growth in long scenarios.
+    pub fn with_history(mut self) -> Self {
+        self.state.record_history = true;
+        self
+    }
+
+    /// Freeze or unfreeze slot and round advancement on price updates
+    ///
+    /// While frozen, `set_price` (and everything built on it) rewrites the
+    /// latest round in place instead of opening a new one, simulating several
+    /// updates within the same slot. Unfrozen (the default) every update
+    /// advances the round and takes the SVM clock's slot.
+    pub fn freeze_slot(&mut self, frozen: bool) {
+        self.state.slot_frozen = frozen;
+    }
+
     /// Create a new price feed (aggregator) account
     pub fn create_price_feed(&mut self, conf: PriceConf) -> Pubkey {
         let keypair = Keypair::new();
-        let pubkey = keypair.pubkey();
+        self.create_feed(keypair.pubkey(), conf, self.program_id)
+    }
 
-        let clock = self.svm.get_sysvar::<Clock>();
-        let aggregator = SwitchboardAggregator::from_conf(&conf, &clock);
-        self.set_account(&pubkey, &aggregator);
-        self.price_feeds.insert(pubkey, aggregator);
+    /// Create a new price feed account, returning an error instead of panicking
+    ///
+    /// Fails with `InvalidPriceData` for an invalid config and `SvmError` when
+    /// the SVM rejects the account.
+    pub fn try_create_price_feed(&mut self, conf: PriceConf) -> Result<Pubkey, ShadowOracleError> {
+        let keypair = Keypair::new();
+        self.try_create_feed(keypair.pubkey(), conf, self.program_id)
+    }
 
-        pubkey
+    /// Create a price feed at a specific address, returning an error instead of panicking
+    ///
+    /// See [`try_create_price_feed`](Self::try_create_price_feed). Unlike
+    /// [`create_price_feed_at`](Self::create_price_feed_at), this refuses an
+    /// address that already holds a tracked feed with `FeedAlreadyExists`.
+    pub fn try_create_price_feed_at(
+        &mut self,
+        address: Pubkey,
+        conf: PriceConf,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        if self.state.contains(&address) {
+            return Err(ShadowOracleError::FeedAlreadyExists(address.to_string()));
+        }
+        self.try_create_feed(address, conf, self.program_id)
     }
 
     /// Create a price feed at a specific address
+    ///
+    /// If the address already holds a tracked feed, that feed is replaced: its
+    /// account is rewritten from `conf` and its metadata and history start
+    /// over. Two feeds never share one address.
     pub fn create_price_feed_at(&mut self, address: Pubkey, conf: PriceConf) -> Pubkey {
-        let clock = self.svm.get_sysvar::<Clock>();
-        let aggregator = SwitchboardAggregator::from_conf(&conf, &clock);
-        self.set_account(&address, &aggregator);
-        self.price_feeds.insert(address, aggregator);
-        address
+        self.create_feed(address, conf, self.program_id)
+    }
+
+    /// Create a new price feed account with a label
+    ///
+    /// The label is shown by [`describe`](Self::describe) and in
+    /// `PriceFeedNotFound` errors raised through [`crate::ShadowOracle`].
+    pub fn create_price_feed_labeled(&mut self, label: &str, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        self.state.set_label(&feed, Some(label.to_string()));
+        feed
+    }
+
+    /// Create a new labeled price feed, returning an error instead of panicking
+    ///
+    /// See [`try_create_price_feed`](Self::try_create_price_feed).
+    pub fn try_create_price_feed_labeled(
+        &mut self,
+        label: &str,
+        conf: PriceConf,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let feed = self.try_create_price_feed(conf)?;
+        self.state.set_label(&feed, Some(label.to_string()));
+        Ok(feed)
+    }
+
+    /// Create a new price feed funded with exactly the rent-exempt minimum
+    ///
+    /// The balance is `Rent::minimum_balance(data_len)` from the SVM's rent
+    /// sysvar, as a program creating the account would fund it, rather than
+    /// the 1 SOL other constructors deposit. Later updates keep the balance.
+    pub fn create_price_feed_rent_exempt(&mut self, conf: PriceConf) -> Pubkey {
+        let feed = self.create_price_feed(conf);
+        let state = self.state.price_feeds.get_mut(&feed).unwrap();
+        state.rent_exempt = true;
+        let state_clone = state.clone();
+        self.set_account(&feed, &state_clone)
+            .expect("Failed to set account");
+        feed
+    }
+
+    /// Create a new price feed account owned by `owner` instead of the provider's program
+    ///
+    /// Useful when feeds in one test belong to different programs, e.g. an
+    /// oracle adapter loaded into the SVM.
+    pub fn create_price_feed_owned_by(&mut self, owner: Pubkey, conf: PriceConf) -> Pubkey {
+        let keypair = Keypair::new();
+        self.create_feed(keypair.pubkey(), conf, owner)
+    }
+
+    /// Create a price feed at the PDA derived from `seeds` and `program_id`
+    ///
+    /// Returns the derived address together with its bump seed.
+    pub fn create_price_feed_at_pda(
+        &mut self,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        conf: PriceConf,
+    ) -> (Pubkey, u8) {
+        let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+        self.create_price_feed_at(address, conf);
+        (address, bump)
     }
 
     /// Update the price of an existing feed
+    ///
+    /// Returns `InvalidPriceData` for a negative std deviation.
     pub fn set_price(
         &mut self,
         feed: &Pubkey,
         price: f64,
         std_dev: f64,
     ) -> Result<(), ShadowOracleError> {
+        crate::price::check_confidence(std_dev)?;
         let clock = self.svm.get_sysvar::<Clock>();
+        let slot_frozen = self.state.slot_frozen;
+        let record_history = self.state.record_history;
         let account = self
+            .state
             .price_feeds
             .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        account.set_price(price, std_dev, &clock);
+        if slot_frozen {
+            account.overwrite_price(price, std_dev);
+        } else {
+            account.set_price(price, std_dev, &clock);
+        }
+        account.last_update_slot = clock.slot;
+        if record_history {
+            account.record_price();
+        }
         let account_clone = account.clone();
-        self.set_account(feed, &account_clone);
-        Ok(())
+        self.set_account(feed, &account_clone)
+    }
+
+    /// Update only the std deviation of a feed, leaving price, round and slot unchanged
+    ///
+    /// Returns `InvalidPriceData` for a negative std deviation.
+    pub fn set_std_deviation(
+        &mut self,
+        feed: &Pubkey,
+        std_dev: f64,
+    ) -> Result<(), ShadowOracleError> {
+        crate::price::check_confidence(std_dev)?;
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.std_deviation = std_dev;
+        account.update_count += 1;
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
+
+    /// Set the min and max oracle responses of the latest round
+    ///
+    /// Leaves the result untouched, so the bounds need not contain it. They
+    /// reset to the new price on the next `set_price`. Returns
+    /// `InvalidPriceData` if `min > max`.
+    pub fn set_result_bounds(
+        &mut self,
+        feed: &Pubkey,
+        min: f64,
+        max: f64,
+    ) -> Result<(), ShadowOracleError> {
+        if min > max {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "min response {min} exceeds max response {max}"
+            )));
+        }
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.min_response = min;
+        account.max_response = max;
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
     }
 
     /// Alias for set_price with USD naming convention
+    ///
+    /// The account stores values rounded to the feed's decimals, so digits
+    /// beyond them are silently dropped; see
+    /// [`set_price_usd_checked`](Self::set_price_usd_checked). A feed created
+    /// with [`PriceConf::with_tick_size`] first rounds the price to its tick.
     pub fn set_price_usd(
         &mut self,
         feed: &Pubkey,
         price: f64,
         std_dev: f64,
     ) -> Result<(), ShadowOracleError> {
+        let price = crate::price::round_to_tick(price, self.state.tick_size(feed));
         self.set_price(feed, price, std_dev)
     }
 
+    /// Like [`set_price_usd`](Self::set_price_usd), but returns
+    /// `InvalidPriceData` when the price or std deviation has more precision
+    /// than the feed's decimals can represent, or the price is off the tick
+    /// set by [`PriceConf::with_tick_size`]
+    pub fn set_price_usd_checked(
+        &mut self,
+        feed: &Pubkey,
+        price: f64,
+        std_dev: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let decimals = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .decimals;
+        crate::price::check_fits_expo(price, -(decimals as i32))?;
+        crate::price::check_fits_expo(std_dev, -(decimals as i32))?;
+        crate::price::check_on_tick(price, self.state.tick_size(feed))?;
+        self.set_price_usd(feed, price, std_dev)
+    }
+
+    /// Get the number of feeds this provider tracks
+    pub fn len(&self) -> usize {
+        self.state.price_feeds.len()
+    }
+
+    /// Check whether this provider tracks no feeds
+    pub fn is_empty(&self) -> bool {
+        self.state.price_feeds.is_empty()
+    }
+
+    /// Check whether this provider tracks `feed`
+    pub fn contains(&self, feed: &Pubkey) -> bool {
+        self.state.price_feeds.contains_key(feed)
+    }
+
     /// Get the current price from a feed
     pub fn get_price(&self, feed: &Pubkey) -> Option<(f64, f64)> {
-        self.price_feeds
+        self.state.price_usd(feed)
+    }
+
+    /// Get the current std deviation of a feed
+    pub fn get_std_deviation(&self, feed: &Pubkey) -> Option<f64> {
+        self.state.price_feeds.get(feed).map(|a| a.std_deviation)
+    }
+
+    /// Get the result and std deviation from the account stored in the SVM
+    ///
+    /// Unlike [`get_price`](Self::get_price), this deserializes the account a
+    /// program would read rather than the provider's cache. Like a program's
+    /// owner check, it returns `None` when the account is not owned by this
+    /// provider's program ID, including feeds created with
+    /// [`create_price_feed_owned_by`](Self::create_price_feed_owned_by).
+    pub fn get_price_from_svm(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        let account = self.svm.get_account(feed)?;
+        if account.owner != self.program_id {
+            return None;
+        }
+        SwitchboardAggregator::read_result(&account.data)
+    }
+
+    /// Get the min and max oracle responses of the latest round
+    pub fn get_result_bounds(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.state
+            .price_feeds
             .get(feed)
-            .map(|a| (a.price, a.std_deviation))
+            .map(|a| (a.min_response, a.max_response))
     }
 
     /// Alias for get_price (already in USD)
@@ -193,198 +653,1798 @@ impl<'a> Switchboard<'a> {
         self.get_price(feed)
     }
 
+    /// Get the confidence analog of a feed in USD: its std deviation
+    ///
+    /// The same accessor exists on every provider: Pyth reports its aggregate
+    /// confidence, Switchboard its std deviation and Chainlink zero.
+    /// Switchboard has no EMA, so there is no EMA confidence counterpart.
+    pub fn conf_usd(&self, feed: &Pubkey) -> Option<f64> {
+        self.state.price_usd(feed).map(|(_, std_dev)| std_dev)
+    }
+
+    /// Get the current price and std deviation as exact decimals
+    ///
+    /// Built from the SwitchboardDecimal mantissa and scale written to the
+    /// account, with no floating-point step on the read side.
+    #[cfg(feature = "decimal")]
+    pub fn get_price_decimal(&self, feed: &Pubkey) -> Option<(crate::Decimal, crate::Decimal)> {
+        let account = self.state.price_feeds.get(feed)?;
+        let expo = -(account.decimals as i32);
+        Some((
+            crate::price::decimal_from_mantissa(account.mantissa(account.price), expo)?,
+            crate::price::decimal_from_mantissa(account.mantissa(account.std_deviation), expo)?,
+        ))
+    }
+
+    /// Get a provider-agnostic reading of a feed
+    ///
+    /// Switchboard has no trading status, so the reading reports `Trading`.
+    pub fn reading(&self, feed: &Pubkey) -> Option<PriceReading> {
+        let account = self.state.price_feeds.get(feed)?;
+        Some(PriceReading {
+            price_usd: account.price,
+            conf_usd: account.std_deviation,
+            expo: -(account.decimals as i32),
+            slot: account.slot,
+            timestamp: account.timestamp,
+            status: PriceStatus::Trading,
+        })
+    }
+
+    /// Reconstruct a `PriceConf` from a feed's current state
+    ///
+    /// The inverse of [`create_price_feed`](Self::create_price_feed): price,
+    /// std deviation (as confidence), decimals and publish time come from the
+    /// current aggregator, with `expo` set to `-decimals`. Switchboard has no
+    /// EMA or trading status, so those fields are left at their defaults.
+    pub fn reading_as_conf(&self, feed: &Pubkey) -> Option<PriceConf> {
+        self.state.reading_as_conf(feed)
+    }
+
+    /// Get the std deviation band as `(price - std_dev, price + std_dev)` in USD
+    pub fn price_bounds_usd(&self, feed: &Pubkey) -> Option<(f64, f64)> {
+        self.get_price(feed)
+            .map(|(price, std_dev)| (price - std_dev, price + std_dev))
+    }
+
     /// Get the timestamp of the last price update
     pub fn get_timestamp(&self, feed: &Pubkey) -> Option<i64> {
-        self.price_feeds.get(feed).map(|a| a.timestamp)
+        self.state.price_feeds.get(feed).map(|a| a.timestamp)
     }
 
     /// Get the slot of the last price update
     pub fn get_slot(&self, feed: &Pubkey) -> Option<u64> {
-        self.price_feeds.get(feed).map(|a| a.slot)
+        self.state.price_feeds.get(feed).map(|a| a.slot)
     }
 
-    /// Make an existing feed stale by setting its timestamp to `seconds_ago` in the past
+    /// Set the `round_open_slot` of the latest confirmed round
     ///
-    /// This is useful for testing staleness checks without changing the price.
-    pub fn make_stale(&mut self, feed: &Pubkey, seconds_ago: i64) -> Result<(), ShadowOracleError> {
-        let clock = self.svm.get_sysvar::<Clock>();
-        let stale_timestamp = clock.unix_timestamp - seconds_ago;
-
+    /// Unlike `set_price`, this leaves the price, timestamp and round ID alone,
+    /// so slot-based staleness can be tested independently of time.
+    pub fn set_round_slot(&mut self, feed: &Pubkey, slot: u64) -> Result<(), ShadowOracleError> {
         let account = self
+            .state
             .price_feeds
             .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        account.timestamp = stale_timestamp;
+        account.slot = slot;
 
         let account_clone = account.clone();
-        self.set_account(feed, &account_clone);
-        Ok(())
+        self.set_account(feed, &account_clone)
     }
 
-    /// Create standard price feeds for common assets
-    pub fn create_standard_feeds(&mut self) -> StandardFeeds {
-        StandardFeeds {
-            sol: self.create_price_feed(PriceConf::new_usd(100.0, 0.1)),
-            btc: self.create_price_feed(PriceConf::new_usd(43000.0, 10.0)),
-            eth: self.create_price_feed(PriceConf::new_usd(2200.0, 1.0)),
-            usdc: self.create_price_feed(PriceConf::stablecoin()),
-            usdt: self.create_price_feed(PriceConf::stablecoin()),
-        }
+    /// Open a new round that has not yet received any oracle responses
+    ///
+    /// Writes a `current_round` with `num_success = 0` and `is_closed = 0`,
+    /// opened at the current slot and time, while `latest_confirmed_round`
+    /// keeps the previous result. Close it with
+    /// [`confirm_round`](Self::confirm_round). Opening again replaces the
+    /// pending round, and `set_price` discards it.
+    pub fn open_round(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let clock = self.svm.get_sysvar::<Clock>();
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.open_round = Some((clock.slot, clock.unix_timestamp));
+
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
     }
 
-    /// Simulate a price crash
-    pub fn simulate_crash(
+    /// Close the open round with a result, making it the latest confirmed round
+    ///
+    /// The confirmed round keeps the slot and timestamp it was opened at.
+    /// Returns `InvalidPriceData` if no round is open or for a negative std
+    /// deviation.
+    pub fn confirm_round(
         &mut self,
         feed: &Pubkey,
-        crash_percent: f64,
+        price: f64,
+        std_dev: f64,
     ) -> Result<(), ShadowOracleError> {
-        let (current_price, current_std) = self
-            .get_price(feed)
+        crate::price::check_confidence(std_dev)?;
+        let clock = self.svm.get_sysvar::<Clock>();
+        let record_history = self.state.record_history;
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
             .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
 
-        let new_price = current_price * (1.0 - crash_percent / 100.0);
-        let new_std = current_std * 5.0;
+        let (slot, timestamp) = account.open_round.ok_or_else(|| {
+            ShadowOracleError::InvalidPriceData(format!("no open round on {feed}"))
+        })?;
+        account.set_price(price, std_dev, &clock);
+        account.slot = slot;
+        account.timestamp = timestamp;
+        account.last_update_slot = clock.slot;
+        if record_history {
+            account.record_price();
+        }
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
 
-        self.set_price(feed, new_price, new_std)
+    /// Get the price, std deviation and timestamp of the round before the
+    /// latest one
+    ///
+    /// Each new round stashes the one it replaces; updates within a frozen
+    /// slot (see [`freeze_slot`](Self::freeze_slot)) rewrite the latest round
+    /// and leave this unchanged. `None` until the feed has had a second round,
+    /// or for unknown feeds.
+    pub fn get_previous_round(&self, feed: &Pubkey) -> Option<(f64, f64, i64)> {
+        self.state.price_feeds.get(feed)?.previous_round
     }
 
-    /// Simulate a depeg for stablecoins
-    pub fn simulate_depeg(
-        &mut self,
-        feed: &Pubkey,
-        new_price: f64,
-    ) -> Result<(), ShadowOracleError> {
-        self.set_price(feed, new_price, (1.0 - new_price).abs() * 0.1 + 0.001)
+    /// Check whether a feed has an open round awaiting confirmation
+    pub fn is_round_open(&self, feed: &Pubkey) -> Option<bool> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|a| a.open_round.is_some())
     }
 
-    fn set_account(&mut self, pubkey: &Pubkey, account: &SwitchboardAggregator) {
-        let data = account.to_bytes();
+    /// Get the `round_open_slot` of the latest confirmed round
+    pub fn get_round_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|a| a.slot)
+    }
 
-        self.svm
-            .set_account(
-                *pubkey,
-                Account {
-                    lamports: 1_000_000_000,
-                    data,
-                    owner: self.program_id,
-                    executable: false,
-                    rent_epoch: 0,
-                },
-            )
-            .expect("Failed to set account");
+    /// Get the program that owns a feed's account
+    pub fn get_owner(&self, feed: &Pubkey) -> Option<Pubkey> {
+        self.state.price_feeds.get(feed).map(|a| a.owner)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get the data length of a feed's account as stored in the SVM
+    ///
+    /// Reads the live account, so it reflects size overrides and any later
+    /// overwrite of the account data.
+    pub fn stored_len(&self, feed: &Pubkey) -> Option<usize> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        self.svm.get_account(feed).map(|account| account.data.len())
+    }
 
-    #[test]
-    fn test_create_price_feed() {
-        let mut svm = LiteSVM::new().with_sysvars();
-        let mut sb = Switchboard::new(&mut svm);
+    /// SHA-256 of a feed's account data in the SVM, for golden-file tests
+    ///
+    /// `None` if the feed is not tracked or its account is missing. Slots and
+    /// timestamps make the hash differ between runs; see
+    /// [`account_hash_stable`](Self::account_hash_stable).
+    pub fn account_hash(&self, feed: &Pubkey) -> Option<[u8; 32]> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        let account = self.svm.get_account(feed)?;
+        Some(super::account_hash(&account.data, &[]))
+    }
 
-        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// SHA-256 of a feed's account data with its volatile fields zeroed
+    ///
+    /// Like [`account_hash`](Self::account_hash), but with the open slot and
+    /// timestamp of both rounds zeroed first, so the hash is the same across
+    /// runs and only changes with the price data or the account layout.
+    pub fn account_hash_stable(&self, feed: &Pubkey) -> Option<[u8; 32]> {
+        if !self.state.contains(feed) {
+            return None;
+        }
+        let account = self.svm.get_account(feed)?;
+        Some(super::account_hash(&account.data, &VOLATILE_FIELDS))
+    }
 
-        let (price, _) = sb.get_price(&feed).unwrap();
-        assert!((price - 100.0).abs() < 0.001);
+    /// Get the slot at which a feed was created
+    pub fn created_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.created_slot)
     }
 
-    #[test]
-    fn test_update_price() {
-        let mut svm = LiteSVM::new().with_sysvars();
-        let mut sb = Switchboard::new(&mut svm);
+    /// Get the slot of the last `set_price` call, or the creation slot if the
+    /// feed was never updated
+    pub fn last_update_slot(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|f| f.last_update_slot)
+    }
 
-        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        sb.set_price_usd(&feed, 150.0, 0.2).unwrap();
+    /// Get the prices recorded for a feed, oldest first
+    ///
+    /// Includes the creation price. Empty if history was not enabled with
+    /// [`with_history`](Self::with_history); `None` for unknown feeds.
+    pub fn history(&self, feed: &Pubkey) -> Option<&[PricePoint]> {
+        self.state
+            .price_feeds
+            .get(feed)
+            .map(|f| f.price_log.as_slice())
+    }
+
+    /// Get the config a feed was created from
+    ///
+    /// This is the `PriceConf` passed at creation with the provider template
+    /// applied; later updates do not change it.
+    pub fn config_for(&self, feed: &Pubkey) -> Option<&PriceConf> {
+        self.state.price_feeds.get(feed).map(|f| &f.config)
+    }
+
+    /// Get the number of price updates applied to a feed since creation
+    pub fn update_count(&self, feed: &Pubkey) -> Option<u64> {
+        self.state.price_feeds.get(feed).map(|a| a.update_count)
+    }
+
+    /// Get the label of a feed, defaulting to its address for unlabeled feeds
+    pub fn label(&self, feed: &Pubkey) -> Option<String> {
+        self.state.price_feeds.get(feed)?;
+        Some(
+            self.state
+                .label(feed)
+                .map_or_else(|| feed.to_string(), str::to_string),
+        )
+    }
+
+    /// Describe a feed's current state in one line, for test output
+    pub fn describe(&self, feed: &Pubkey) -> Option<String> {
+        let (price, std_dev) = self.get_price(feed)?;
+        Some(format!(
+            "{}: ${price} ± {std_dev}, slot {}, timestamp {}",
+            super::feed_name(feed, self.state.label(feed)),
+            self.get_slot(feed)?,
+            self.get_timestamp(feed)?,
+        ))
+    }
+
+    /// Check whether a feed holds a price consumers would accept
+    ///
+    /// A Switchboard price is valid when the latest confirmed round has at
+    /// least one successful oracle response (`num_success > 0`).
+    pub fn is_valid(&self, feed: &Pubkey) -> Option<bool> {
+        self.state.price_feeds.get(feed).map(|a| a.num_success > 0)
+    }
+
+    /// Make an existing feed stale by setting its timestamp to `seconds_ago` in the past
+    ///
+    /// This is useful for testing staleness checks without changing the price.
+    pub fn make_stale(&mut self, feed: &Pubkey, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+        let clock = self.svm.get_sysvar::<Clock>();
+        let stale_timestamp = clock.unix_timestamp - seconds_ago;
+
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.timestamp = stale_timestamp;
+
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
+
+    /// Set a feed's timestamp `seconds_ahead` in the future of the SVM clock
+    ///
+    /// Models an oracle publishing future timestamps to dodge staleness
+    /// checks, for testing that a program rejects them. The mirror image of
+    /// [`make_stale`](Self::make_stale); the price is unchanged.
+    pub fn make_future(
+        &mut self,
+        feed: &Pubkey,
+        seconds_ahead: i64,
+    ) -> Result<(), ShadowOracleError> {
+        self.make_stale(feed, -seconds_ahead)
+    }
+
+    /// Make every feed tracked by this provider stale, as in a total oracle outage
+    ///
+    /// Applies [`make_stale`](Self::make_stale) to each feed.
+    pub fn make_all_stale(&mut self, seconds_ago: i64) -> Result<(), ShadowOracleError> {
+        let feeds: Vec<Pubkey> = self.state.price_feeds.keys().copied().collect();
+        for feed in feeds {
+            self.make_stale(&feed, seconds_ago)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a total outage: zero the feed's account data and hand the
+    /// account to the system program
+    ///
+    /// Reads that check the owner, such as
+    /// [`get_price_from_svm`](Self::get_price_from_svm), fail until the feed
+    /// is brought back with [`restore_feed`](Self::restore_feed). The cached
+    /// feed is kept; any price update also rewrites the account.
+    pub fn simulate_outage(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        if !self.state.contains(feed) {
+            return Err(ShadowOracleError::PriceFeedNotFound(feed.to_string()));
+        }
+
+        let mut account = self.svm.get_account(feed).unwrap_or_default();
+        account.data.fill(0);
+        // The system program's id is all zeros
+        account.owner = Pubkey::default();
+        self.svm
+            .set_account(*feed, account)
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+
+    /// Rewrite a feed's account from the cached state, ending a
+    /// [`simulate_outage`](Self::simulate_outage)
+    pub fn restore_feed(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let state = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .clone();
+        self.set_account(feed, &state)
+    }
+
+    /// Save a feed's current price, std deviation and `num_success`, to return
+    /// to with [`recover_baseline`](Self::recover_baseline) after a
+    /// `simulate_*` stress
+    ///
+    /// Replaces any baseline captured earlier.
+    pub fn capture_baseline(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.baseline = Some((account.price, account.std_deviation, account.num_success));
+        Ok(())
+    }
+
+    /// Publish the price, std deviation and `num_success` saved by
+    /// [`capture_baseline`](Self::capture_baseline) as a new round at the
+    /// current clock
+    ///
+    /// Returns `InvalidPriceData` if no baseline was captured. The baseline
+    /// is kept, so a feed can be stressed and recovered repeatedly.
+    pub fn recover_baseline(&mut self, feed: &Pubkey) -> Result<(), ShadowOracleError> {
+        let (price, std_dev, num_success) = self
+            .state
+            .price_feeds
+            .get(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?
+            .baseline
+            .ok_or_else(|| {
+                ShadowOracleError::InvalidPriceData(format!("no baseline captured for {feed}"))
+            })?;
+
+        self.set_price(feed, price, std_dev)?;
+        let account = self.state.price_feeds.get_mut(feed).unwrap();
+        account.num_success = num_success;
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
+
+    /// Create standard price feeds for common assets
+    pub fn create_standard_feeds(&mut self) -> StandardFeeds {
+        StandardFeeds {
+            sol: self.create_price_feed(PriceConf::new_usd(100.0, 0.1)),
+            btc: self.create_price_feed(PriceConf::new_usd(43000.0, 10.0)),
+            eth: self.create_price_feed(PriceConf::new_usd(2200.0, 1.0)),
+            usdc: self.create_price_feed(PriceConf::stablecoin()),
+            usdt: self.create_price_feed(PriceConf::stablecoin()),
+        }
+    }
+
+    /// Create standard price feeds with a std deviation proportional to each price
+    ///
+    /// Same prices as [`create_standard_feeds`](Self::create_standard_feeds),
+    /// with a 0.1% band on SOL, BTC and ETH and a 0.01% band on USDC and USDT.
+    pub fn create_standard_feeds_realistic(&mut self) -> StandardFeeds {
+        let major = |price: f64| PriceConf::new_usd(price, price * 0.001);
+        let stable = PriceConf::stablecoin_with_conf(0.0001);
+        StandardFeeds {
+            sol: self.create_price_feed(major(100.0)),
+            btc: self.create_price_feed(major(43000.0)),
+            eth: self.create_price_feed(major(2200.0)),
+            usdc: self.create_price_feed(stable.clone()),
+            usdt: self.create_price_feed(stable),
+        }
+    }
+
+    /// Create standard price feeds at their mainnet addresses from [`crate::feeds::switchboard`]
+    ///
+    /// The `feeds` module has no Switchboard stablecoin addresses, so USDC and USDT
+    /// are created at random addresses.
+    pub fn create_standard_feeds_at_mainnet(&mut self) -> StandardFeeds {
+        use crate::feeds::switchboard as mainnet;
+
+        StandardFeeds {
+            sol: self.create_price_feed_at(mainnet::sol_usd(), PriceConf::new_usd(100.0, 0.1)),
+            btc: self.create_price_feed_at(mainnet::btc_usd(), PriceConf::new_usd(43000.0, 10.0)),
+            eth: self.create_price_feed_at(mainnet::eth_usd(), PriceConf::new_usd(2200.0, 1.0)),
+            usdc: self.create_price_feed(PriceConf::stablecoin()),
+            usdt: self.create_price_feed(PriceConf::stablecoin()),
+        }
+    }
+
+    /// Simulate a price crash
+    pub fn simulate_crash(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        let (current_price, current_std) = self
+            .get_price(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let new_price = current_price * (1.0 - crash_percent / 100.0);
+        let new_std = current_std * 5.0;
+
+        self.set_price(feed, new_price, new_std)
+    }
+
+    /// Simulate a severe crash in which the feed halts
+    ///
+    /// Applies [`simulate_crash`](Self::simulate_crash) (price down by
+    /// `crash_percent`, std deviation widened 5x), then marks the latest
+    /// round as having no successful oracle responses (`num_success = 0`),
+    /// Switchboard's encoding of an invalid result. The next
+    /// [`set_price`](Self::set_price) confirms a round with responses again.
+    pub fn simulate_crash_and_halt(
+        &mut self,
+        feed: &Pubkey,
+        crash_percent: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.simulate_crash(feed, crash_percent)?;
+        let account = self
+            .state
+            .price_feeds
+            .get_mut(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        account.num_success = 0;
+        let account_clone = account.clone();
+        self.set_account(feed, &account_clone)
+    }
+
+    /// Drive a feed along an arbitrary path, one update per slot
+    ///
+    /// For each slot from `start_slot` to `end_slot` inclusive, warps the SVM
+    /// to that slot and sets the price to `f(slot)`, keeping the current std
+    /// deviation. This advances the SVM clock, leaving it at `end_slot`.
+    pub fn simulate_with(
+        &mut self,
+        feed: &Pubkey,
+        start_slot: u64,
+        end_slot: u64,
+        f: impl Fn(u64) -> f64,
+    ) -> Result<(), ShadowOracleError> {
+        let (_, std_dev) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        for slot in start_slot..=end_slot {
+            self.svm.warp_to_slot(slot);
+            self.set_price_usd(feed, f(slot), std_dev)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate the price impact of a swap against a pool
+    ///
+    /// Moves the price by `trade_size_usd / liquidity_usd`: the new price is
+    /// `price * (1 + trade_size_usd / liquidity_usd)`. A positive trade size is
+    /// a buy and pushes the price up, a negative one is a sell and pushes it
+    /// down. The std deviation is unchanged. Returns `InvalidPriceData` unless
+    /// `trade_size_usd` is finite and `liquidity_usd` is positive.
+    pub fn simulate_price_impact(
+        &mut self,
+        feed: &Pubkey,
+        trade_size_usd: f64,
+        liquidity_usd: f64,
+    ) -> Result<(), ShadowOracleError> {
+        if !trade_size_usd.is_finite() {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "trade size must be finite, got {trade_size_usd}"
+            )));
+        }
+        if liquidity_usd <= 0.0 || liquidity_usd.is_nan() {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "liquidity must be positive, got {liquidity_usd}"
+            )));
+        }
+        let (price, std_dev) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let new_price = price * (1.0 + trade_size_usd / liquidity_usd);
+        self.set_price_usd(feed, new_price, std_dev)
+    }
+
+    /// Simulate a depeg for stablecoins
+    ///
+    /// The std deviation widens with the distance from $1.00: 10% of it plus
+    /// $0.001. Use [`simulate_depeg_with`](Self::simulate_depeg_with) to
+    /// choose the std deviation.
+    pub fn simulate_depeg(
+        &mut self,
+        feed: &Pubkey,
+        new_price: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.simulate_depeg_with(feed, new_price, (1.0 - new_price).abs() * 0.1 + 0.001)
+    }
+
+    /// Simulate a depeg for stablecoins with an exact std deviation in USD
+    ///
+    /// Nothing is added to `std_dev`, so a zero std deviation depeg tests
+    /// rejection on price alone.
+    pub fn simulate_depeg_with(
+        &mut self,
+        feed: &Pubkey,
+        new_price: f64,
+        std_dev: f64,
+    ) -> Result<(), ShadowOracleError> {
+        self.set_price(feed, new_price, std_dev)
+    }
+
+    /// Simulate a degrading feed: std deviation widened by `std_multiplier` and
+    /// the timestamp aged by `stale_seconds`, with the price unchanged
+    pub fn simulate_degradation(
+        &mut self,
+        feed: &Pubkey,
+        std_multiplier: f64,
+        stale_seconds: i64,
+    ) -> Result<(), ShadowOracleError> {
+        let std_dev = self
+            .get_std_deviation(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        self.set_std_deviation(feed, std_dev * std_multiplier)?;
+        self.make_stale(feed, stale_seconds)
+    }
+
+    /// Simulate a stablecoin jittering around its peg
+    ///
+    /// Random-walks the price for `steps` updates, each moving it by up to half
+    /// of `band_usd`, keeping the current std deviation. The walk starts from
+    /// the current price and is clamped to `[1 - band_usd, 1 + band_usd]`: a
+    /// step that would leave the band stops at its edge, so the feed never
+    /// depegs further than the band. The same `seed` always produces the same
+    /// path. Each update is written one slot after the previous one, advancing
+    /// the SVM clock. Returns `InvalidPriceData` unless `0 <= band_usd < 1`.
+    pub fn simulate_peg_jitter(
+        &mut self,
+        feed: &Pubkey,
+        band_usd: f64,
+        steps: u32,
+        seed: u64,
+    ) -> Result<(), ShadowOracleError> {
+        if !(0.0..1.0).contains(&band_usd) {
+            return Err(ShadowOracleError::InvalidPriceData(format!(
+                "peg band must be in [0, 1), got {band_usd}"
+            )));
+        }
+        let (start, std_dev) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let (low, high) = (1.0 - band_usd, 1.0 + band_usd);
+        let mut rng = SplitMix64::new(seed);
+        let mut price = start.clamp(low, high);
+        for step in 0..steps {
+            if step > 0 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + 1);
+            }
+            price = (price + rng.next_signed_unit() * band_usd / 2.0).clamp(low, high);
+            self.set_price_usd(feed, price, std_dev)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate a stablecoin gradually re-pegging to $1.00
+    ///
+    /// Moves the price an equal fraction of the way back to $1.00 on each of
+    /// `steps` updates, ending exactly at $1.00, narrowing the std deviation as
+    /// it goes. Each step is written one slot after the previous one,
+    /// advancing the SVM clock, so per-slot reads see the ramp.
+    pub fn simulate_repeg(&mut self, feed: &Pubkey, steps: u32) -> Result<(), ShadowOracleError> {
+        let (start, _) = self
+            .get_price(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        for step in 1..=steps {
+            if step > 1 {
+                let slot = self.svm.get_sysvar::<Clock>().slot;
+                self.svm.warp_to_slot(slot + 1);
+            }
+            let price = start + (1.0 - start) * step as f64 / steps as f64;
+            self.simulate_depeg(feed, price)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate an intraday range, walking the price down to `low_usd`, up to
+    /// `high_usd` and back
+    ///
+    /// The walk has three legs of `steps` equal updates each: from the current
+    /// price to `low_usd`, from `low_usd` to `high_usd`, then back to the
+    /// starting price, keeping the current std deviation. Each update is
+    /// written one slot after the previous one, advancing the SVM clock, so
+    /// per-slot reads see both boundary crossings.
+    pub fn simulate_range(
+        &mut self,
+        feed: &Pubkey,
+        low_usd: f64,
+        high_usd: f64,
+        steps: u32,
+    ) -> Result<(), ShadowOracleError> {
+        let (start, std_dev) = self
+            .get_price_usd(feed)
+            .ok_or_else(|| ShadowOracleError::PriceFeedNotFound(feed.to_string()))?;
+
+        let legs = [(start, low_usd), (low_usd, high_usd), (high_usd, start)];
+        let mut first = true;
+        for (from, to) in legs {
+            for step in 1..=steps {
+                if !first {
+                    let slot = self.svm.get_sysvar::<Clock>().slot;
+                    self.svm.warp_to_slot(slot + 1);
+                }
+                first = false;
+                let price = from + (to - from) * step as f64 / steps as f64;
+                self.set_price_usd(feed, price, std_dev)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn create_feed(&mut self, address: Pubkey, conf: PriceConf, owner: Pubkey) -> Pubkey {
+        match self.try_create_feed(address, conf, owner) {
+            Ok(feed) => feed,
+            Err(err @ ShadowOracleError::InvalidPriceData(_)) => {
+                panic!("Invalid price config: {err}")
+            }
+            Err(err) => panic!("Failed to create price feed: {err}"),
+        }
+    }
+
+    fn try_create_feed(
+        &mut self,
+        address: Pubkey,
+        conf: PriceConf,
+        owner: Pubkey,
+    ) -> Result<Pubkey, ShadowOracleError> {
+        let conf = conf.with_template(&self.template)?;
+        conf.validate()?;
+        let clock = self.svm.get_sysvar::<Clock>();
+        let mut aggregator = SwitchboardAggregator::from_conf(&conf, &clock, owner);
+        if self.state.record_history {
+            aggregator.record_price();
+        }
+        self.set_account(&address, &aggregator)?;
+        self.state.price_feeds.insert(address, aggregator);
+        Ok(address)
+    }
+
+    fn set_account(
+        &mut self,
+        pubkey: &Pubkey,
+        account: &SwitchboardAggregator,
+    ) -> Result<(), ShadowOracleError> {
+        let data = account.to_bytes();
+
+        let lamports = if account.rent_exempt {
+            self.svm.minimum_balance_for_rent_exemption(data.len())
+        } else {
+            1_000_000_000
+        };
+
+        self.svm
+            .set_account(
+                *pubkey,
+                Account {
+                    lamports,
+                    data,
+                    owner: account.owner,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .map_err(|err| ShadowOracleError::SvmError(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_price_feed_rent_exempt() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let funded = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let exact = sb.create_price_feed_rent_exempt(PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(sb.svm.get_account(&funded).unwrap().lamports, 1_000_000_000);
+        let account = sb.svm.get_account(&exact).unwrap();
+        let minimum = sb
+            .svm
+            .minimum_balance_for_rent_exemption(account.data.len());
+        assert_eq!(account.lamports, minimum);
+        assert!(minimum < 1_000_000_000);
+
+        // Updates keep the rent-exempt balance
+        sb.set_price_usd(&exact, 110.0, 0.1).unwrap();
+        assert_eq!(sb.svm.get_account(&exact).unwrap().lamports, minimum);
+    }
+
+    #[test]
+    fn test_create_price_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let (price, _) = sb.get_price(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_to_switchboard_bytes() {
+        let conf = PriceConf {
+            publish_time: Some(1_700_000_000),
+            ..PriceConf::new_usd(100.0, 0.5)
+        };
+        let data = conf.to_switchboard_bytes();
+
+        let (price, std_dev) = SwitchboardAggregator::read_result(&data).unwrap();
+        assert!((price - 100.0).abs() < 1e-9);
+        assert!((std_dev - 0.5).abs() < 1e-9);
+
+        // Same bytes a provider writes at slot 0
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(conf);
+        assert_eq!(sb.svm.get_account(&feed).unwrap().data, data);
+    }
+
+    #[test]
+    fn test_discriminator() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let data = sb.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[0..8], Switchboard::discriminator());
+    }
+
+    #[test]
+    fn test_update_price() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.set_price_usd(&feed, 150.0, 0.2).unwrap();
+
+        let (price, _) = sb.get_price_usd(&feed).unwrap();
+        assert!((price - 150.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_standard_feeds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feeds = sb.create_standard_feeds();
+
+        let (sol_price, _) = sb.get_price_usd(&feeds.sol).unwrap();
+        assert!((sol_price - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_standard_feeds_realistic() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feeds = sb.create_standard_feeds_realistic();
+
+        for (feed, price, ratio) in [
+            (feeds.sol, 100.0, 0.001),
+            (feeds.btc, 43000.0, 0.001),
+            (feeds.eth, 2200.0, 0.001),
+            (feeds.usdc, 1.0, 0.0001),
+            (feeds.usdt, 1.0, 0.0001),
+        ] {
+            let (actual, conf) = sb.get_price_usd(&feed).unwrap();
+            assert!((actual - price).abs() < 0.001);
+            assert!((conf - price * ratio).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_simulate_crash_and_halt() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.simulate_crash_and_halt(&feed, 40.0).unwrap();
+
+        let (price, std_dev) = sb.get_price(&feed).unwrap();
+        assert!((price - 60.0).abs() < 0.001);
+        assert!((std_dev - 0.5).abs() < 0.001);
+        assert_eq!(sb.is_valid(&feed), Some(false));
+        let data = sb.svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[ROUND_OFFSET..ROUND_OFFSET + 4], 0u32.to_le_bytes());
+
+        sb.set_price(&feed, 65.0, 0.5).unwrap();
+        assert_eq!(sb.is_valid(&feed), Some(true));
+    }
+
+    #[test]
+    fn test_recover_baseline() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert!(sb.recover_baseline(&feed).is_err());
+
+        sb.capture_baseline(&feed).unwrap();
+        sb.simulate_crash_and_halt(&feed, 40.0).unwrap();
+        sb.recover_baseline(&feed).unwrap();
+
+        let (price, std_dev) = sb.get_price(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+        assert!((std_dev - 0.1).abs() < 0.001);
+        assert_eq!(sb.is_valid(&feed), Some(true));
+    }
+
+    #[test]
+    fn test_simulate_crash() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.simulate_crash(&feed, 50.0).unwrap();
+
+        let (price, _) = sb.get_price_usd(&feed).unwrap();
+        assert!((price - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_timestamp_uses_svm_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let initial_clock = svm.get_sysvar::<Clock>();
+        let initial_timestamp = initial_clock.unix_timestamp;
+
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let feed_timestamp = sb.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, initial_timestamp);
+    }
+
+    #[test]
+    fn test_slot_uses_svm_clock() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let initial_clock = svm.get_sysvar::<Clock>();
+        let initial_slot = initial_clock.slot;
+
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        let feed_slot = sb.get_slot(&feed).unwrap();
+        assert_eq!(feed_slot, initial_slot);
+    }
+
+    #[test]
+    fn test_make_stale() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let clock = svm.get_sysvar::<Clock>();
+        let current_time = clock.unix_timestamp;
+
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        // Make the feed 5 minutes stale
+        sb.make_stale(&feed, 300).unwrap();
+
+        let feed_timestamp = sb.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, current_time - 300);
+    }
+
+    #[test]
+    fn test_create_stale_feed_with_stale_by() {
+        let mut svm = LiteSVM::new().with_sysvars();
+
+        let clock = svm.get_sysvar::<Clock>();
+        let current_time = clock.unix_timestamp;
+
+        let mut sb = Switchboard::new(&mut svm);
+
+        // Create a feed that's already 5 minutes old
+        let stale_conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, current_time);
+        let feed = sb.create_price_feed(stale_conf);
+
+        let feed_timestamp = sb.get_timestamp(&feed).unwrap();
+        assert_eq!(feed_timestamp, current_time - 300);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.is_valid(&feed), Some(true));
+        assert_eq!(sb.is_valid(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_create_price_feed_at_pda() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"oracle", b"SOL"];
+        let (address, bump) =
+            sb.create_price_feed_at_pda(seeds, &program_id, PriceConf::new_usd(100.0, 0.1));
+
+        assert_eq!(
+            (address, bump),
+            Pubkey::find_program_address(seeds, &program_id)
+        );
+        assert!(sb.get_price(&address).is_some());
+        assert!(svm.get_account(&address).is_some());
+    }
+
+    #[test]
+    fn test_update_count() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.update_count(&feed), Some(0));
+
+        sb.set_price_usd(&feed, 110.0, 0.1).unwrap();
+        sb.simulate_crash(&feed, 50.0).unwrap();
+        assert_eq!(sb.update_count(&feed), Some(2));
+    }
+
+    #[test]
+    fn test_set_round_slot() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let timestamp = sb.get_timestamp(&feed).unwrap();
+        sb.set_round_slot(&feed, 42).unwrap();
+
+        assert_eq!(sb.get_round_slot(&feed), Some(42));
+        assert_eq!(sb.get_timestamp(&feed), Some(timestamp));
+
+        let data = svm.get_account(&feed).unwrap().data;
+        assert_eq!(data[1153..1161], 42u64.to_le_bytes());
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_get_price_decimal() {
+        use std::str::FromStr;
+
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(2));
+        sb.set_price(&feed, 100.25, 0.5).unwrap();
+
+        let (price, std_dev) = sb.get_price_decimal(&feed).unwrap();
+        assert_eq!(price, crate::Decimal::from_str("100.25").unwrap());
+        assert_eq!(std_dev, crate::Decimal::from_str("0.50").unwrap());
+    }
+
+    #[test]
+    fn test_price_bounds_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.2));
+        let (lower, upper) = sb.price_bounds_usd(&feed).unwrap();
+        assert!((lower - 99.8).abs() < 0.001);
+        assert!((upper - 100.2).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_freeze_slot() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let slot = sb.get_slot(&feed).unwrap();
+
+        sb.freeze_slot(true);
+        sb.svm.warp_to_slot(slot + 10);
+        sb.set_price(&feed, 101.0, 0.1).unwrap();
+        assert_eq!(sb.get_slot(&feed), Some(slot));
+        assert!((sb.get_price(&feed).unwrap().0 - 101.0).abs() < 0.001);
+
+        sb.freeze_slot(false);
+        sb.set_price(&feed, 101.0, 0.1).unwrap();
+        assert_eq!(sb.get_slot(&feed), Some(slot + 10));
+    }
+
+    #[test]
+    fn test_try_create_price_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
 
-        let (price, _) = sb.get_price_usd(&feed).unwrap();
-        assert!((price - 150.0).abs() < 0.001);
+        let feed = sb
+            .try_create_price_feed(PriceConf::new_usd(100.0, 0.1))
+            .unwrap();
+        assert!(sb.contains(&feed));
+
+        assert!(matches!(
+            sb.try_create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(40)),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+
+        // The SVM refuses feed data at a sysvar address
+        let slot_hashes = Pubkey::from_str("SysvarS1otHashes111111111111111111111111111").unwrap();
+        let result = sb.try_create_price_feed_at(slot_hashes, PriceConf::new_usd(100.0, 0.1));
+        assert!(matches!(result, Err(ShadowOracleError::SvmError(_))));
+        assert!(!sb.contains(&slot_hashes));
+        assert_eq!(sb.len(), 1);
     }
 
     #[test]
-    fn test_standard_feeds() {
+    fn test_create_at_existing_address() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut sb = Switchboard::new(&mut svm);
 
-        let feeds = sb.create_standard_feeds();
+        let address = Pubkey::new_unique();
+        sb.create_price_feed_at(address, PriceConf::new_usd(100.0, 0.1));
+        sb.set_price_usd(&address, 101.0, 0.1).unwrap();
+
+        // Creating again replaces the feed rather than aliasing it
+        sb.create_price_feed_at(address, PriceConf::new_usd(200.0, 0.1));
+        assert_eq!(sb.len(), 1);
+        assert_eq!(sb.update_count(&address), Some(0));
+        let (price, _) = sb.get_price_usd(&address).unwrap();
+        assert!((price - 200.0).abs() < 0.001);
+
+        // The fallible variant refuses and leaves the feed untouched
+        assert!(matches!(
+            sb.try_create_price_feed_at(address, PriceConf::new_usd(300.0, 0.1)),
+            Err(ShadowOracleError::FeedAlreadyExists(_))
+        ));
+        let (price, _) = sb.get_price_usd(&address).unwrap();
+        assert!((price - 200.0).abs() < 0.001);
+    }
 
-        let (sol_price, _) = sb.get_price_usd(&feeds.sol).unwrap();
-        assert!((sol_price - 100.0).abs() < 0.001);
+    #[test]
+    fn test_open_and_confirm_round() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.is_round_open(&feed), Some(false));
+
+        sb.svm.warp_to_slot(10);
+        sb.open_round(&feed).unwrap();
+        assert_eq!(sb.is_round_open(&feed), Some(true));
+
+        // The pending round has no responses, the confirmed result is unchanged
+        let data = sb.svm.get_account(&feed).unwrap().data;
+        let current = CURRENT_ROUND_OFFSET;
+        assert_eq!(data[current..current + 4], 0u32.to_le_bytes());
+        assert_eq!(data[current + 8], 0);
+        assert_eq!(data[current + 9..current + 17], 10u64.to_le_bytes());
+        assert_eq!(data[ROUND_OFFSET + 8], 1);
+        let (price, _) = sb.get_price_from_svm(&feed).unwrap();
+        assert!((price - 100.0).abs() < 1e-6);
+
+        sb.svm.warp_to_slot(12);
+        sb.confirm_round(&feed, 105.0, 0.2).unwrap();
+        assert_eq!(sb.is_round_open(&feed), Some(false));
+        assert_eq!(sb.get_round_slot(&feed), Some(10));
+        let (price, std_dev) = sb.get_price_from_svm(&feed).unwrap();
+        assert!((price - 105.0).abs() < 1e-6);
+        assert!((std_dev - 0.2).abs() < 1e-6);
+
+        // Once closed, current_round mirrors the confirmed round
+        let data = sb.svm.get_account(&feed).unwrap().data;
+        assert_eq!(
+            data[current..current + ROUND_SIZE],
+            data[ROUND_OFFSET..ROUND_OFFSET + ROUND_SIZE]
+        );
+
+        assert!(matches!(
+            sb.confirm_round(&feed, 106.0, 0.2),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
     }
 
     #[test]
-    fn test_simulate_crash() {
+    fn test_stored_len() {
         let mut svm = LiteSVM::new().with_sysvars();
         let mut sb = Switchboard::new(&mut svm);
 
         let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
-        sb.simulate_crash(&feed, 50.0).unwrap();
+        assert_eq!(sb.stored_len(&feed), Some(3851));
 
-        let (price, _) = sb.get_price_usd(&feed).unwrap();
-        assert!((price - 50.0).abs() < 0.001);
+        // Reflects the account as stored, not the expected layout
+        let mut account = sb.svm.get_account(&feed).unwrap();
+        account.data.truncate(64);
+        sb.svm.set_account(feed, account).unwrap();
+        assert_eq!(sb.stored_len(&feed), Some(64));
+
+        assert_eq!(sb.stored_len(&Pubkey::new_unique()), None);
     }
 
     #[test]
-    fn test_timestamp_uses_svm_clock() {
+    fn test_create_price_feed_owned_by() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
 
-        let initial_clock = svm.get_sysvar::<Clock>();
-        let initial_timestamp = initial_clock.unix_timestamp;
+        let adapter = Pubkey::new_unique();
+        let owned = sb.create_price_feed_owned_by(adapter, PriceConf::new_usd(100.0, 0.1));
+        let default = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.get_owner(&owned), Some(adapter));
+        assert_eq!(sb.get_owner(&default), Some(sb.program_id));
+
+        // The owner survives price updates
+        sb.simulate_crash(&owned, 10.0).unwrap();
+        assert_eq!(svm.get_account(&owned).unwrap().owner, adapter);
+    }
+
+    #[test]
+    fn test_set_price_usd_tick_size() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_tick_size(0.05));
+        sb.set_price_usd(&feed, 100.017, 0.1).unwrap();
+
+        let (price, std_dev) = sb.get_price_from_svm(&feed).unwrap();
+        assert!((price - 100.0).abs() < 1e-9);
+        assert!((std_dev - 0.1).abs() < 1e-9);
+        sb.set_price_usd(&feed, 100.03, 0.1).unwrap();
+        assert!((sb.get_price(&feed).unwrap().0 - 100.05).abs() < 1e-9);
 
+        // The rounded tick survives scaling to the feed's decimals
+        let cents = sb.create_price_feed(PriceConf::new_usd(1.0, 0.01).with_tick_size(0.01));
+        sb.set_price_usd(&cents, 0.287, 0.01).unwrap();
+        assert_eq!(sb.get_price_from_svm(&cents), Some((0.29, 0.01)));
+    }
+
+    #[test]
+    fn test_set_price_usd_checked() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_quoted(1.0, 0.01, -4));
+        let result = sb.set_price_usd_checked(&feed, 1.23456, 0.01);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(sb.set_price_usd_checked(&feed, 1.2345, 0.00001).is_err());
+        assert_eq!(sb.update_count(&feed), Some(0));
+
+        sb.set_price_usd_checked(&feed, 1.2345, 0.0001).unwrap();
+        assert_eq!(sb.get_price_from_svm(&feed).map(|(p, _)| p), Some(1.2345));
+
+        // An accepted value is stored exactly, not truncated to 0.28999999
+        let usd = sb.create_price_feed(PriceConf::new_usd(1.0, 0.01));
+        sb.set_price_usd_checked(&usd, 0.29, 0.01).unwrap();
+        assert_eq!(sb.get_price_from_svm(&usd), Some((0.29, 0.01)));
+        assert!(sb
+            .set_price_usd_checked(&Pubkey::new_unique(), 1.0, 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_price_usd_checked_tick_size() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_tick_size(0.05));
+        assert!(matches!(
+            sb.set_price_usd_checked(&feed, 100.02, 0.1),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(sb.update_count(&feed), Some(0));
+
+        sb.set_price_usd_checked(&feed, 100.05, 0.1).unwrap();
+        assert_eq!(sb.get_price_from_svm(&feed), Some((100.05, 0.1)));
+    }
+
+    #[test]
+    fn test_set_price_usd_rejects_negative_confidence() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut sb = Switchboard::new(&mut svm);
+
         let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let result = sb.set_price_usd(&feed, 100.0, -0.1);
+        assert!(matches!(
+            result,
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert_eq!(sb.update_count(&feed), Some(0));
+    }
 
-        let feed_timestamp = sb.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, initial_timestamp);
+    #[test]
+    fn test_simulate_peg_jitter() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let a = sb.create_price_feed(PriceConf::stablecoin());
+        let b = sb.create_price_feed(PriceConf::stablecoin());
+        let c = sb.create_price_feed(PriceConf::stablecoin());
+        let slot = sb.svm.get_sysvar::<Clock>().slot;
+        sb.simulate_peg_jitter(&a, 0.002, 50, 7).unwrap();
+        assert_eq!(sb.svm.get_sysvar::<Clock>().slot, slot + 49);
+        sb.simulate_peg_jitter(&b, 0.002, 50, 7).unwrap();
+        sb.simulate_peg_jitter(&c, 0.002, 50, 8).unwrap();
+
+        let (price_a, price_b, price_c) = (
+            sb.get_price_usd(&a).unwrap().0,
+            sb.get_price_usd(&b).unwrap().0,
+            sb.get_price_usd(&c).unwrap().0,
+        );
+        assert_eq!(price_a, price_b);
+        assert_ne!(price_a, price_c);
+        for price in [price_a, price_c] {
+            assert!((0.998..=1.002).contains(&price));
+        }
+
+        assert!(sb
+            .simulate_peg_jitter(&Pubkey::new_unique(), 0.002, 5, 7)
+            .is_err());
+        let feed = sb.create_price_feed(PriceConf::stablecoin());
+        assert!(matches!(
+            sb.simulate_peg_jitter(&feed, 1.5, 5, 7),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
     }
 
     #[test]
-    fn test_slot_uses_svm_clock() {
+    fn test_simulate_depeg_with() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
 
-        let initial_clock = svm.get_sysvar::<Clock>();
-        let initial_slot = initial_clock.slot;
+        let feed = sb.create_price_feed(PriceConf::stablecoin());
+        sb.simulate_depeg_with(&feed, 0.95, 0.0).unwrap();
+        assert_eq!(sb.get_price(&feed), Some((0.95, 0.0)));
+        assert_eq!(sb.get_price_from_svm(&feed), Some((0.95, 0.0)));
+
+        sb.simulate_depeg(&feed, 0.9).unwrap();
+        assert!((sb.get_std_deviation(&feed).unwrap() - 0.011).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_get_previous_round() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.5));
+        assert_eq!(sb.get_previous_round(&feed), None);
+
+        let mut clock = sb.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 10;
+        sb.svm.set_sysvar(&clock);
+        sb.set_price(&feed, 101.0, 0.6).unwrap();
+        assert_eq!(
+            sb.get_previous_round(&feed),
+            Some((100.0, 0.5, 1_700_000_000))
+        );
+
+        sb.set_price(&feed, 102.0, 0.7).unwrap();
+        assert_eq!(
+            sb.get_previous_round(&feed),
+            Some((101.0, 0.6, 1_700_000_010))
+        );
+
+        // Rewriting the latest round in a frozen slot keeps the previous round
+        sb.freeze_slot(true);
+        sb.set_price(&feed, 103.0, 0.8).unwrap();
+        assert_eq!(
+            sb.get_previous_round(&feed),
+            Some((101.0, 0.6, 1_700_000_010))
+        );
+        assert_eq!(sb.get_previous_round(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_simulate_repeg() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::stablecoin());
+        sb.simulate_depeg(&feed, 0.9).unwrap();
+        let (_, depeg_std) = sb.get_price(&feed).unwrap();
+        let slot = sb.get_slot(&feed).unwrap();
+
+        sb.simulate_repeg(&feed, 4).unwrap();
+
+        let (price, std_dev) = sb.get_price(&feed).unwrap();
+        assert!((price - 1.0).abs() < 0.001);
+        assert!(std_dev < depeg_std);
+        assert_eq!(sb.get_slot(&feed).unwrap(), slot + 3);
+        assert_eq!(sb.update_count(&feed), Some(5));
+    }
+
+    #[test]
+    fn test_labeled_feed() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let labeled = sb.create_price_feed_labeled("SOL/USD", PriceConf::new_usd(100.0, 0.1));
+        let plain = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
 
+        assert_eq!(sb.label(&labeled).as_deref(), Some("SOL/USD"));
+        assert_eq!(sb.label(&plain), Some(plain.to_string()));
+        assert_eq!(sb.label(&Pubkey::new_unique()), None);
+
+        let description = sb.describe(&labeled).unwrap();
+        assert!(description.starts_with(&format!("SOL/USD ({labeled}): $100")));
+        assert!(sb.describe(&plain).unwrap().starts_with(&plain.to_string()));
+    }
+
+    #[test]
+    fn test_set_std_deviation() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut sb = Switchboard::new(&mut svm);
+
         let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let round = sb.get_round_slot(&feed);
+        sb.set_std_deviation(&feed, 5.0).unwrap();
 
-        let feed_slot = sb.get_slot(&feed).unwrap();
-        assert_eq!(feed_slot, initial_slot);
+        assert_eq!(sb.get_std_deviation(&feed), Some(5.0));
+        let (price, _) = sb.get_price(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+        assert_eq!(sb.get_round_slot(&feed), round);
+
+        // A variance guard rejecting std_dev above 1% of price now trips
+        let (price, std_dev) = sb.get_price(&feed).unwrap();
+        assert!(std_dev / price > 0.01);
+
+        // Result mantissa unchanged, std mantissa widened (scale 8)
+        let data = sb.svm.get_account(&feed).unwrap().data;
+        let result = i128::from_le_bytes(data[1169..1185].try_into().unwrap());
+        let std = i128::from_le_bytes(data[1201..1217].try_into().unwrap());
+        assert_eq!(result, 10_000_000_000);
+        assert_eq!(std, 500_000_000);
+
+        assert!(sb.set_std_deviation(&feed, -1.0).is_err());
+        assert!(sb.set_std_deviation(&Pubkey::new_unique(), 1.0).is_err());
     }
 
     #[test]
-    fn test_make_stale() {
+    fn test_reading() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
 
-        let clock = svm.get_sysvar::<Clock>();
-        let current_time = clock.unix_timestamp;
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.5));
+
+        let reading = sb.reading(&feed).unwrap();
+        assert!((reading.price_usd - 100.0).abs() < 0.001);
+        assert!((reading.conf_usd - 0.5).abs() < 0.001);
+        assert_eq!(reading.expo, -8);
+        assert_eq!(reading.slot, sb.get_slot(&feed).unwrap());
+        assert_eq!(reading.timestamp, sb.get_timestamp(&feed).unwrap());
+        assert_eq!(reading.status, PriceStatus::Trading);
+        assert!(sb.reading(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_reading_as_conf() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_quoted(100.0, 0.5, -6));
+        sb.set_price(&feed, 90.1, 0.2).unwrap();
+
+        let conf = sb.reading_as_conf(&feed).unwrap();
+        assert_eq!(conf.price, 90_100_000);
+        assert_eq!(conf.conf, 200_000);
+        assert_eq!(conf.expo, -6);
+        assert_eq!(conf.decimals, 6);
+        assert_eq!(conf.publish_time, sb.get_timestamp(&feed));
+
+        let copy = sb.create_price_feed(conf);
+        let (price, std_dev) = sb.get_price(&copy).unwrap();
+        assert!((price - 90.1).abs() < 1e-9);
+        assert!((std_dev - 0.2).abs() < 1e-9);
+        assert!(sb.reading_as_conf(&Pubkey::new_unique()).is_none());
+    }
 
+    #[test]
+    fn test_simulate_outage() {
+        let mut svm = LiteSVM::new().with_sysvars();
         let mut sb = Switchboard::new(&mut svm);
+
         let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        let expected = sb.get_price_from_svm(&feed);
+        assert!(expected.is_some());
+
+        sb.simulate_outage(&feed).unwrap();
+        assert_eq!(sb.get_price_from_svm(&feed), None);
+        let account = sb.svm.get_account(&feed).unwrap();
+        assert_eq!(account.owner, Pubkey::default());
+        assert!(account.data.iter().all(|b| *b == 0));
+
+        sb.restore_feed(&feed).unwrap();
+        assert_eq!(sb.get_price_from_svm(&feed), expected);
+
+        let missing = Pubkey::new_unique();
+        assert!(matches!(
+            sb.simulate_outage(&missing),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+        assert!(sb.restore_feed(&missing).is_err());
+    }
 
-        // Make the feed 5 minutes stale
-        sb.make_stale(&feed, 300).unwrap();
+    #[test]
+    fn test_conf_usd() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
 
-        let feed_timestamp = sb.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, current_time - 300);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.25));
+        assert_eq!(sb.conf_usd(&feed), Some(0.25));
+        sb.set_price(&feed, 101.0, 0.5).unwrap();
+        assert_eq!(sb.conf_usd(&feed), Some(0.5));
+        assert_eq!(sb.conf_usd(&Pubkey::new_unique()), None);
     }
 
     #[test]
-    fn test_create_stale_feed_with_stale_by() {
+    fn test_make_future() {
         let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
 
-        let clock = svm.get_sysvar::<Clock>();
-        let current_time = clock.unix_timestamp;
+        let mut sb = Switchboard::new(&mut svm);
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.make_future(&feed, 120).unwrap();
+
+        let now = sb.svm.get_sysvar::<Clock>().unix_timestamp;
+        let timestamp = sb.get_timestamp(&feed).unwrap();
+        assert!(timestamp > now);
+        assert_eq!(timestamp, 1_700_000_120);
+        assert!(sb.make_future(&Pubkey::new_unique(), 120).is_err());
+    }
+
+    #[test]
+    fn test_account_hash() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
 
         let mut sb = Switchboard::new(&mut svm);
+        let first = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.svm.warp_to_slot(50);
+        let mut clock = sb.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 30;
+        sb.svm.set_sysvar(&clock);
+        let second = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        assert_ne!(sb.account_hash(&first), sb.account_hash(&second));
+        let stable = sb.account_hash_stable(&first).unwrap();
+        assert_eq!(sb.account_hash_stable(&second), Some(stable));
+
+        sb.set_price_usd(&second, 101.0, 0.1).unwrap();
+        assert_ne!(sb.account_hash_stable(&second), Some(stable));
+        assert_eq!(sb.account_hash(&Pubkey::new_unique()), None);
+        assert_eq!(sb.account_hash_stable(&Pubkey::new_unique()), None);
+    }
 
-        // Create a feed that's already 5 minutes old
-        let stale_conf = PriceConf::new_usd(100.0, 0.1).stale_by(300, current_time);
-        let feed = sb.create_price_feed(stale_conf);
+    #[test]
+    fn test_make_all_stale() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let current_time = svm.get_sysvar::<Clock>().unix_timestamp;
+        let mut sb = Switchboard::new(&mut svm);
 
-        let feed_timestamp = sb.get_timestamp(&feed).unwrap();
-        assert_eq!(feed_timestamp, current_time - 300);
+        let feeds: Vec<Pubkey> = (0..3)
+            .map(|_| sb.create_price_feed(PriceConf::new_usd(100.0, 0.1)))
+            .collect();
+        sb.make_all_stale(600).unwrap();
+
+        for feed in &feeds {
+            assert_eq!(sb.get_timestamp(feed), Some(current_time - 600));
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(0.0, 0.0));
+
+        assert!(sb.contains(&feed));
+        assert!(!sb.contains(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_created_and_last_update_slot() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        svm.warp_to_slot(100);
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.created_slot(&feed), Some(100));
+        assert_eq!(sb.last_update_slot(&feed), Some(100));
+
+        sb.svm.warp_to_slot(250);
+        sb.set_price_usd(&feed, 110.0, 0.1).unwrap();
+        assert_eq!(sb.created_slot(&feed), Some(100));
+        assert_eq!(sb.last_update_slot(&feed), Some(250));
+    }
+
+    #[test]
+    fn test_simulate_degradation() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let current_time = svm.get_sysvar::<Clock>().unix_timestamp;
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.5));
+        sb.simulate_degradation(&feed, 4.0, 120).unwrap();
+
+        let (price, std_dev) = sb.get_price(&feed).unwrap();
+        assert!((price - 100.0).abs() < 0.001);
+        assert!((std_dev - 2.0).abs() < 0.001);
+        assert_eq!(sb.get_timestamp(&feed), Some(current_time - 120));
+
+        assert!(sb
+            .simulate_degradation(&Pubkey::new_unique(), 2.0, 60)
+            .is_err());
+    }
+
+    #[test]
+    fn test_len() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+        assert!(sb.is_empty());
+
+        sb.create_standard_feeds();
+        assert_eq!(sb.len(), 5);
+        assert!(!sb.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "decimals 40 exceeds")]
+    fn test_create_rejects_out_of_range_decimals() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        sb.create_price_feed(PriceConf::new_usd(100.0, 0.1).with_decimals(40));
+    }
+
+    #[test]
+    fn test_get_price_from_svm() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.set_price(&feed, 123.45, 0.6).unwrap();
+
+        let (price, std_dev) = sb.get_price_from_svm(&feed).unwrap();
+        assert!((price - 123.45).abs() < 1e-6);
+        assert!((std_dev - 0.6).abs() < 1e-6);
+
+        assert_eq!(sb.get_price_from_svm(&Pubkey::new_unique()), None);
+
+        // Accounts owned by another program fail the owner check
+        let foreign =
+            sb.create_price_feed_owned_by(Pubkey::new_unique(), PriceConf::new_usd(100.0, 0.1));
+        assert!(sb.get_price(&foreign).is_some());
+        assert_eq!(sb.get_price_from_svm(&foreign), None);
+    }
+
+    #[test]
+    fn test_simulate_with() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm).with_history();
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.simulate_with(&feed, 10, 14, |s| 100.0 + s as f64)
+            .unwrap();
+
+        let history = sb.history(&feed).unwrap();
+        assert_eq!(history.len(), 6);
+        for (point, slot) in history[1..].iter().zip(10..=14) {
+            assert_eq!(point.slot, slot);
+            assert!((point.price_usd - (100.0 + slot as f64)).abs() < 0.001);
+        }
+        assert_eq!(sb.get_slot(&feed), Some(14));
+        assert_eq!(sb.svm.get_sysvar::<Clock>().slot, 14);
+    }
+
+    #[test]
+    fn test_simulate_price_impact() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+
+        // A $50k buy into $1M of liquidity moves the price up 5%
+        sb.simulate_price_impact(&feed, 50_000.0, 1_000_000.0)
+            .unwrap();
+        let (price, _) = sb.get_price_usd(&feed).unwrap();
+        assert!((price - 105.0).abs() < 0.001);
+
+        // A sell of the same size moves it down 5% from there
+        sb.simulate_price_impact(&feed, -50_000.0, 1_000_000.0)
+            .unwrap();
+        let (price, _) = sb.get_price_usd(&feed).unwrap();
+        assert!((price - 99.75).abs() < 0.001);
+
+        assert!(matches!(
+            sb.simulate_price_impact(&feed, 1.0, 0.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(matches!(
+            sb.simulate_price_impact(&feed, f64::NAN, 1_000_000.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+        assert!(matches!(
+            sb.simulate_price_impact(&feed, f64::INFINITY, 1_000_000.0),
+            Err(ShadowOracleError::InvalidPriceData(_))
+        ));
+    }
+
+    #[test]
+    fn test_simulate_range() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm).with_history();
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.simulate_range(&feed, 90.0, 110.0, 2).unwrap();
+
+        let history = sb.history(&feed).unwrap();
+        let prices: Vec<f64> = history.iter().map(|p| p.price_usd).collect();
+        let slots: Vec<u64> = history.iter().map(|p| p.slot).collect();
+        assert_eq!(prices.len(), 7);
+        for (price, expected) in prices
+            .iter()
+            .zip([100.0, 95.0, 90.0, 100.0, 110.0, 105.0, 100.0])
+        {
+            assert!((price - expected).abs() < 0.001);
+        }
+        assert_eq!(slots, vec![0, 0, 1, 2, 3, 4, 5]);
+
+        assert!(matches!(
+            sb.simulate_range(&Pubkey::new_unique(), 90.0, 110.0, 2),
+            Err(ShadowOracleError::PriceFeedNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm).with_history();
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.svm.warp_to_slot(10);
+        sb.set_price_usd(&feed, 90.0, 0.1).unwrap();
+        sb.svm.warp_to_slot(20);
+        sb.set_price_usd(&feed, 95.0, 0.1).unwrap();
+
+        let history = sb.history(&feed).unwrap();
+        let prices: Vec<f64> = history.iter().map(|p| p.price_usd).collect();
+        let slots: Vec<u64> = history.iter().map(|p| p.slot).collect();
+        assert_eq!(prices.len(), 3);
+        for (price, expected) in prices.iter().zip([100.0, 90.0, 95.0]) {
+            assert!((price - expected).abs() < 0.001);
+        }
+        assert_eq!(slots, vec![0, 10, 20]);
+        assert_eq!(sb.history(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_history_off_by_default() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.set_price_usd(&feed, 90.0, 0.1).unwrap();
+
+        assert_eq!(sb.history(&feed), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_set_result_bounds() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.get_result_bounds(&feed), Some((100.0, 100.0)));
+
+        sb.set_result_bounds(&feed, 80.0, 125.0).unwrap();
+        assert_eq!(sb.get_result_bounds(&feed), Some((80.0, 125.0)));
+
+        // min_response and max_response follow std_deviation (scale 8)
+        let data = sb.svm.get_account(&feed).unwrap().data;
+        let min = i128::from_le_bytes(
+            data[RESULT_OFFSET + 64..RESULT_OFFSET + 80]
+                .try_into()
+                .unwrap(),
+        );
+        let max = i128::from_le_bytes(
+            data[RESULT_OFFSET + 96..RESULT_OFFSET + 112]
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(min, 8_000_000_000);
+        assert_eq!(max, 12_500_000_000);
+
+        // A new round resets the bounds
+        sb.set_price(&feed, 101.0, 0.1).unwrap();
+        assert_eq!(sb.get_result_bounds(&feed), Some((101.0, 101.0)));
+
+        assert!(sb.set_result_bounds(&feed, 110.0, 90.0).is_err());
+    }
+
+    #[test]
+    fn test_config_for() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb =
+            Switchboard::new(&mut svm).with_template(PriceConf::default().with_decimals(6));
+
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        sb.set_price_usd(&feed, 90.0, 0.1).unwrap();
+
+        let config = sb.config_for(&feed).unwrap();
+        assert_eq!(config.price, 10_000_000_000);
+        assert_eq!(config.decimals, 6);
+        assert!(sb.config_for(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_program_id_default() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        assert_eq!(
+            Switchboard::program_id_default().to_string(),
+            SWITCHBOARD_PROGRAM_ID
+        );
+        let feed = sb.create_price_feed(PriceConf::new_usd(100.0, 0.1));
+        assert_eq!(sb.get_owner(&feed), Some(Switchboard::program_id_default()));
+    }
+
+    #[test]
+    fn test_create_standard_feeds_at_mainnet() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut sb = Switchboard::new(&mut svm);
+
+        let feeds = sb.create_standard_feeds_at_mainnet();
+        assert_eq!(feeds.sol, crate::feeds::switchboard::sol_usd());
+        assert_eq!(feeds.btc, crate::feeds::switchboard::btc_usd());
+        assert_eq!(feeds.eth, crate::feeds::switchboard::eth_usd());
+        assert_eq!(sb.len(), 5);
+
+        let (price, _) = sb.get_price_usd(&feeds.btc).unwrap();
+        assert!((price - 43000.0).abs() < 0.001);
     }
 }
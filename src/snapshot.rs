@@ -0,0 +1,172 @@
+//! JSON export and import of every feed tracked by a [`ShadowOracle`]
+
+use crate::{Chainlink, PriceConf, Provider, Pyth, ShadowOracle, ShadowOracleError, Switchboard};
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+use std::str::FromStr;
+
+/// Serialized form of a whole oracle
+#[derive(Serialize, Deserialize)]
+struct OracleSnapshot {
+    feeds: Vec<FeedSnapshot>,
+}
+
+/// One feed: where it lives and the config that recreates its current price
+#[derive(Serialize, Deserialize)]
+struct FeedSnapshot {
+    provider: Provider,
+    /// Base58 feed address
+    address: String,
+    label: Option<String>,
+    conf: PriceConf,
+}
+
+impl ShadowOracle<'_> {
+    /// Export every tracked feed as a JSON document, for sharing a scenario
+    ///
+    /// Each feed is stored as its provider, address, label and current state
+    /// as a `PriceConf` (see `reading_as_conf`). Feeds are listed by provider,
+    /// then address, so the same state always gives the same document.
+    /// Provider-side settings such as owners, history, Switchboard's
+    /// `num_success` or Chainlink's flag are not exported.
+    ///
+    /// # Example
+    /// ```rust
+    /// use litesvm::LiteSVM;
+    /// use shadow_oracle::{PriceConf, ShadowOracle};
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut oracle = ShadowOracle::new(&mut svm);
+    /// let feed = oracle.pyth().create_price_feed(PriceConf::new_usd(100.0, 0.1));
+    /// let json = oracle.to_json();
+    ///
+    /// let mut svm = LiteSVM::new().with_sysvars();
+    /// let mut restored = ShadowOracle::new(&mut svm);
+    /// restored.from_json(&json).unwrap();
+    /// assert_eq!(restored.pyth().get_price_usd(&feed), Some((100.0, 0.1)));
+    /// ```
+    pub fn to_json(&self) -> String {
+        let feeds = self
+            .all_feeds()
+            .into_iter()
+            .filter_map(|(provider, feed, _)| {
+                let (label, conf) = match provider {
+                    Provider::Pyth => (self.pyth.label(&feed), self.pyth.reading_as_conf(&feed)?),
+                    Provider::Switchboard => (
+                        self.switchboard.label(&feed),
+                        self.switchboard.reading_as_conf(&feed)?,
+                    ),
+                    Provider::Chainlink => (
+                        self.chainlink.label(&feed),
+                        self.chainlink.reading_as_conf(&feed)?,
+                    ),
+                };
+                Some(FeedSnapshot {
+                    provider,
+                    address: feed.to_string(),
+                    label: label.map(str::to_string),
+                    conf,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&OracleSnapshot { feeds })
+            .expect("oracle snapshot serializes to JSON")
+    }
+
+    /// Recreate the feeds of a document produced by [`to_json`](Self::to_json)
+    ///
+    /// Each feed is created at its original address under its provider's
+    /// default program ID, replacing any feed already tracked there. The
+    /// oracle's template is not applied, so exponents round-trip exactly.
+    /// Returns `SerializationError` for malformed JSON or addresses, and
+    /// `InvalidPriceData` for an invalid config; nothing is created then.
+    pub fn from_json(&mut self, json: &str) -> Result<(), ShadowOracleError> {
+        let snapshot: OracleSnapshot = serde_json::from_str(json)
+            .map_err(|err| ShadowOracleError::SerializationError(err.to_string()))?;
+        let feeds = snapshot
+            .feeds
+            .into_iter()
+            .map(|feed| {
+                let address = Pubkey::from_str(&feed.address).map_err(|err| {
+                    ShadowOracleError::SerializationError(format!("{}: {err}", feed.address))
+                })?;
+                feed.conf.validate()?;
+                Ok((address, feed))
+            })
+            .collect::<Result<Vec<_>, ShadowOracleError>>()?;
+
+        for (address, feed) in feeds {
+            match feed.provider {
+                Provider::Pyth => {
+                    Pyth::from_state(self.svm, &mut self.pyth)
+                        .create_price_feed_at(address, feed.conf);
+                    self.pyth.set_label(&address, feed.label);
+                }
+                Provider::Switchboard => {
+                    Switchboard::from_state(self.svm, &mut self.switchboard)
+                        .create_price_feed_at(address, feed.conf);
+                    self.switchboard.set_label(&address, feed.label);
+                }
+                Provider::Chainlink => {
+                    Chainlink::from_state(self.svm, &mut self.chainlink)
+                        .create_price_feed_at(address, feed.conf);
+                    self.chainlink.set_label(&address, feed.label);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PriceStatus;
+    use litesvm::LiteSVM;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut oracle = ShadowOracle::new(&mut svm);
+        oracle.set_clock(1_700_000_000, 100);
+
+        let sol = oracle
+            .pyth()
+            .create_price_feed_labeled("SOL", PriceConf::new_usd(150.0, 0.3));
+        oracle.pyth().set_status(&sol, PriceStatus::Halted).unwrap();
+        let btc = oracle
+            .switchboard()
+            .create_price_feed(PriceConf::new_usd(43000.0, 10.0));
+        let usdc = oracle
+            .chainlink()
+            .create_price_feed(PriceConf::stablecoin());
+        let eur = oracle
+            .pyth()
+            .create_price_feed(PriceConf::new_quoted(1.08, 0.001, -6));
+        let json = oracle.to_json();
+
+        let mut svm = LiteSVM::new().with_sysvars();
+        let mut restored =
+            ShadowOracle::new(&mut svm).with_template(PriceConf::default().with_expo(-4));
+        restored.from_json(&json).unwrap();
+
+        assert_eq!(restored.pyth().get_price_usd(&sol), Some((150.0, 0.3)));
+        assert_eq!(
+            restored.pyth().reading(&sol).unwrap().status,
+            PriceStatus::Halted
+        );
+        assert_eq!(restored.pyth().label(&sol).as_deref(), Some("SOL"));
+        assert_eq!(restored.pyth().get_timestamp(&sol), Some(1_700_000_000));
+        assert_eq!(restored.pyth().get_price(&eur), Some((1_080_000, 1_000)));
+        let (price, _) = restored.switchboard().get_price_usd(&btc).unwrap();
+        assert!((price - 43000.0).abs() < 0.001);
+        let (price, _) = restored.chainlink().get_price_usd(&usdc).unwrap();
+        assert!((price - 1.0).abs() < 0.001);
+        assert_eq!(restored.to_json(), json);
+
+        assert!(matches!(
+            restored.from_json("{"),
+            Err(ShadowOracleError::SerializationError(_))
+        ));
+    }
+}